@@ -0,0 +1,90 @@
+use std::{sync::Arc, time::Instant};
+
+use image::{ImageBuffer, Rgb};
+use rayon::prelude::{IntoParallelIterator, ParallelBridge, ParallelIterator};
+use raytracer::{
+    object::{
+        geometry::{sphere::Sphere, vector::Vector3},
+        material::{
+            color::Color,
+            dielectric::{Dielectric, Ior},
+            diffuse_light::DiffuseLight,
+            lambertian::Lambertian,
+        },
+    },
+    render::{
+        film,
+        integrator::{Integrator, SpectralPathTracer},
+        pixel::Vector3Extension,
+    },
+    util::random::Random,
+    vec3,
+    view::{camera::Camera, ray::HitTarget},
+};
+
+fn main() {
+    let aspect_ratio = 16. / 9.;
+    let image_width = 800;
+    let image_height = (image_width as f64 / aspect_ratio) as u32;
+    let samples = 500;
+    let max_depth = 50;
+
+    let world = scene();
+    let camera = Camera::new(
+        vec3!(0, 2, 8),
+        vec3!(0, 0.8, 0),
+        Vector3::up(),
+        30.,
+        aspect_ratio,
+        0.,
+        8.,
+    );
+
+    let integrator = SpectralPathTracer::new(max_depth);
+
+    let start = Instant::now();
+
+    let mut buffer = ImageBuffer::new(image_width, image_height);
+    buffer
+        .enumerate_pixels_mut()
+        .par_bridge()
+        .into_par_iter()
+        .for_each(|(i, j, pixel)| {
+            let mut color_sum = Vector3::zero();
+            for _ in 0..samples {
+                let (u, v) = film::pixel_uv(
+                    i as usize,
+                    j as usize,
+                    (Random::f64(), Random::f64()),
+                    image_width as usize,
+                    image_height as usize,
+                );
+                color_sum += integrator.radiance(&camera.get_ray(u, v), &world);
+            }
+
+            let sampled_color = color_sum / samples;
+
+            *pixel = Rgb(sampled_color.sqrt().to_u8_range().into());
+        });
+
+    let end = Instant::now();
+
+    buffer.save("dispersion.png").expect("Error saving image");
+    let duration = end - start;
+    println!("Rendered in {} ms", duration.as_millis());
+}
+
+fn scene() -> HitTarget {
+    let mut world = HitTarget::new();
+    let ground = Arc::new(Lambertian::new(Color::white() * 0.7));
+    (*world).push(Arc::new(Sphere::new(vec3!(0, -1000, 0), 1000., ground)));
+
+    let diamond = Arc::new(Dielectric::dispersive(Ior::diamond()));
+    let crown_glass = Arc::new(Dielectric::dispersive(Ior::bk7()));
+    (*world).push(Arc::new(Sphere::new(vec3!(-1.2, 1, 0), 1., diamond)));
+    (*world).push(Arc::new(Sphere::new(vec3!(1.2, 1, 0), 1., crown_glass)));
+
+    let light = Arc::new(DiffuseLight::new(Color::white() * 20.));
+    (*world).push(Arc::new(Sphere::new(vec3!(0, 6, -3), 0.5, light)));
+    world
+}
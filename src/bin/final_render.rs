@@ -1,16 +1,17 @@
-use std::{sync::Arc, time::Instant};
+use std::{env, sync::Arc, time::Instant};
 
 use image::{ImageBuffer, Rgb};
-use rayon::prelude::{IntoParallelIterator, ParallelBridge, ParallelIterator};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 use raytracer::{
     object::{
         geometry::{sphere::Sphere, vector::Vector3},
+        light::{Light, SkyLight},
         material::{
             color::Color, dielectric::Dielectric, lambertian::Lambertian, metal::Metal, Material,
         },
     },
-    render::pixel::Vector3Extension,
-    util::{random::Random, ray_color_diffuse},
+    render::{film, integrator, pixel::Vector3Extension},
+    util::random::Random,
     vec3,
     view::{camera::Camera, ray::HitTarget},
 };
@@ -21,6 +22,7 @@ fn main() {
     let image_height = (image_width as f64 / aspect_ratio) as u32;
     let samples = 500;
     let max_depth = 50;
+    let min_depth = integrator::DEFAULT_MIN_DEPTH;
 
     let world = random_scene();
     let camera = Camera::new(
@@ -33,27 +35,55 @@ fn main() {
         10.,
     );
 
+    // ? Sampling the sky directly needs a bound on the part of the scene
+    // it matters for, the small spheres around the origin
+    let lights: Vec<Arc<dyn Light>> = vec![Arc::new(SkyLight::new(Vector3::zero(), 15.))];
+    let integrator_name = env::args().nth(1).unwrap_or_else(|| "path".into());
+    let settings = integrator::Settings::new(lights, max_depth)
+        .with_min_depth(min_depth)
+        .with_camera(camera.clone(), image_width as usize, image_height as usize);
+    let integrator = integrator::from_name(&integrator_name, settings).unwrap_or_else(|| {
+        panic!(
+            "Unknown integrator, expected one of {:?}",
+            integrator::NAMES
+        )
+    });
+
     let start = Instant::now();
 
-    let mut buffer = ImageBuffer::new(image_width, image_height);
-    buffer
-        .enumerate_pixels_mut()
-        .par_bridge()
-        .into_par_iter()
-        .for_each(|(i, j, pixel)| {
+    let mut pixels = vec![Color::black(); (image_width * image_height) as usize];
+    pixels
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, pixel)| {
+            let i = index as u32 % image_width;
+            let j = index as u32 / image_width;
             let mut color_sum = Vector3::zero();
             for _ in 0..samples {
-                let u = (i as f64 + Random::f64()) / (image_width - 1) as f64;
-                let v = ((image_height - 1 - j) as f64 + Random::f64()) / (image_height - 1) as f64;
+                let (u, v) = film::pixel_uv(
+                    i as usize,
+                    j as usize,
+                    (Random::f64(), Random::f64()),
+                    image_width as usize,
+                    image_height as usize,
+                );
 
                 let ray = camera.get_ray(u, v);
-                color_sum += ray_color_diffuse(&ray, &world, max_depth);
+                color_sum += integrator.radiance(&ray, &world);
             }
 
-            let sampled_color = color_sum / samples;
-
-            *pixel = Rgb(sampled_color.sqrt().to_u8_range().into());
+            *pixel = color_sum / samples;
         });
+    if let Some(splats) = integrator.splats(samples) {
+        for (pixel, splat) in pixels.iter_mut().zip(splats) {
+            *pixel += splat;
+        }
+    }
+
+    let buffer = ImageBuffer::from_fn(image_width, image_height, |i, j| {
+        let color = pixels[(j * image_width + i) as usize];
+        Rgb(color.sqrt().to_u8_range().into())
+    });
 
     let end = Instant::now();
 
@@ -0,0 +1,80 @@
+use std::{env, time::Instant};
+
+use image::{ImageBuffer, Rgb};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use raytracer::{
+    import::pbrt,
+    object::{geometry::vector::Vector3, material::color::Color},
+    render::{film, integrator, pixel::Vector3Extension},
+    util::random::Random,
+};
+
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .expect("Usage: pbrt_scene <scene.pbrt> [output.png] [integrator]");
+    let scene = pbrt::load(&path).expect("Error loading pbrt scene");
+    let output = env::args()
+        .nth(2)
+        .unwrap_or_else(|| "pbrt-scene.png".into());
+    let integrator_name = env::args().nth(3).unwrap_or(scene.integrator.clone());
+    let image_width = scene.image_width;
+    let image_height = scene.image_height;
+    let samples = scene.samples;
+    let settings = integrator::Settings::new(scene.lights.clone(), scene.max_depth)
+        .with_min_depth(scene.min_depth)
+        .with_background(scene.background)
+        .with_camera(
+            scene.camera.clone(),
+            image_width as usize,
+            image_height as usize,
+        );
+    let integrator = integrator::from_name(&integrator_name, settings).unwrap_or_else(|| {
+        panic!(
+            "Unknown integrator, expected one of {:?}",
+            integrator::NAMES
+        )
+    });
+
+    let start = Instant::now();
+
+    let mut pixels = vec![Color::black(); (image_width * image_height) as usize];
+    pixels
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, pixel)| {
+            let i = index as u32 % image_width;
+            let j = index as u32 / image_width;
+            let mut color_sum = Vector3::zero();
+            for _ in 0..samples {
+                let (u, v) = film::pixel_uv(
+                    i as usize,
+                    j as usize,
+                    (Random::f64(), Random::f64()),
+                    image_width as usize,
+                    image_height as usize,
+                );
+
+                let ray = scene.camera.get_ray(u, v);
+                color_sum += integrator.radiance(&ray, &scene.world);
+            }
+
+            *pixel = color_sum / samples;
+        });
+    if let Some(splats) = integrator.splats(samples) {
+        for (pixel, splat) in pixels.iter_mut().zip(splats) {
+            *pixel += splat;
+        }
+    }
+
+    let buffer = ImageBuffer::from_fn(image_width, image_height, |i, j| {
+        let color = pixels[(j * image_width + i) as usize];
+        Rgb(color.sqrt().to_u8_range().into())
+    });
+
+    let end = Instant::now();
+
+    buffer.save(output).expect("Error saving image");
+    let duration = end - start;
+    println!("Rendered in {} ms", duration.as_millis());
+}
@@ -0,0 +1,87 @@
+use std::{sync::Arc, time::Instant};
+
+use image::{ImageBuffer, Rgb};
+use raytracer::{
+    object::{
+        geometry::{sphere::Sphere, vector::Vector3},
+        light::{DirectionalLight, Light},
+        material::{color::Color, toon::Toon},
+    },
+    render::{
+        film,
+        integrator::{DirectLighting, Integrator},
+        outline::{GBuffer, Outline},
+        pixel::Vector3Extension,
+    },
+    vec3,
+    view::{camera::Camera, ray::HitTarget},
+};
+
+fn main() {
+    let aspect_ratio = 16. / 9.;
+    let image_width = 800;
+    let image_height = (image_width as f64 / aspect_ratio) as usize;
+
+    let world = scene();
+    let camera = Camera::new(
+        vec3!(0, 2, 8),
+        vec3!(0, 0.8, 0),
+        Vector3::up(),
+        30.,
+        aspect_ratio,
+        0.,
+        8.,
+    );
+
+    let lights: Vec<Arc<dyn Light>> = vec![Arc::new(DirectionalLight::new(
+        vec3!(1, 2, 1),
+        Color::white() * 0.9,
+        0.,
+    ))];
+    let integrator = DirectLighting::new(lights, 1);
+
+    let start = Instant::now();
+
+    // ? Toon surfaces are only lit by the sharp sun, so one ray per pixel is enough
+    let mut pixels = Vec::with_capacity(image_width * image_height);
+    for j in 0..image_height {
+        for i in 0..image_width {
+            let (u, v) = film::pixel_uv(i, j, (0.5, 0.5), image_width, image_height);
+            pixels.push(integrator.radiance(&camera.get_ray(u, v), &world));
+        }
+    }
+
+    let gbuffer = GBuffer::render(&camera, &world, image_width, image_height);
+    gbuffer.add_ambient(&mut pixels);
+    Outline::default().draw(&gbuffer, &mut pixels);
+
+    let buffer = ImageBuffer::from_fn(image_width as u32, image_height as u32, |i, j| {
+        let color = pixels[j as usize * image_width + i as usize];
+        Rgb(color.sqrt().to_u8_range().into())
+    });
+
+    let end = Instant::now();
+
+    buffer.save("toon-render.png").expect("Error saving image");
+    let duration = end - start;
+    println!("Rendered in {} ms", duration.as_millis());
+}
+
+fn scene() -> HitTarget {
+    let toon = |color: Color| Arc::new(Toon::new(color, 3));
+
+    let mut world = HitTarget::new();
+    let ground = toon(Color::new(0.6, 0.7, 0.5));
+    (*world).push(Arc::new(Sphere::new(vec3!(0, -1000, 0), 1000., ground)));
+    (*world).push(Arc::new(Sphere::new(
+        vec3!(-1.2, 1, 0),
+        1.,
+        toon(Color::new(0.9, 0.4, 0.3)),
+    )));
+    (*world).push(Arc::new(Sphere::new(
+        vec3!(1.2, 0.6, 0.5),
+        0.6,
+        toon(Color::new(0.3, 0.5, 0.9)),
+    )));
+    world
+}
@@ -0,0 +1,41 @@
+use std::{error::Error, fmt::Display, io};
+
+pub mod gltf;
+pub mod merl;
+pub mod pbrt;
+pub mod ply;
+pub mod stl;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    Parse(String),
+    Unsupported(String),
+}
+
+pub type ImportResult<T> = Result<T, ImportError>;
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(error) => write!(f, "I/O error: {error}"),
+            ImportError::Parse(message) => write!(f, "parse error: {message}"),
+            ImportError::Unsupported(message) => write!(f, "unsupported: {message}"),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
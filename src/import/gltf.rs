@@ -0,0 +1,331 @@
+use std::{path::Path, sync::Arc};
+
+use ::gltf::{camera::Projection, image::Format, material::AlphaMode, mesh::Mode, Document, Node};
+
+use crate::{
+    object::{
+        geometry::{
+            mesh::{Mesh, TriangleMesh},
+            transform::Transform,
+            vector::Vector3,
+        },
+        material::{
+            color::Color, cutout::Cutout, lambertian::Lambertian, normal_map::NormalMapped,
+            principled::Principled, Material,
+        },
+        texture::{srgb_to_linear, ImageTexture, SolidColor, Texture},
+    },
+    view::{camera::Camera, ray::HitTarget},
+};
+
+use super::{ImportError, ImportResult};
+
+/// Perspective camera found in a glTF node
+#[derive(Debug, Copy, Clone)]
+pub struct GltfCamera {
+    pub position: Vector3,
+    pub at: Vector3,
+    pub up: Vector3,
+    /// Vertical field of view in degrees
+    pub vfov: f64,
+    pub aspect_ratio: Option<f64>,
+}
+
+impl GltfCamera {
+    /// Builds a pinhole camera, using `aspect_ratio` when the file has none
+    pub fn camera(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.position,
+            self.at,
+            self.up,
+            self.vfov,
+            self.aspect_ratio.unwrap_or(aspect_ratio),
+            0.,
+            1.,
+        )
+    }
+}
+
+pub struct GltfScene {
+    pub world: HitTarget,
+    pub cameras: Vec<GltfCamera>,
+}
+
+struct Importer {
+    document: Document,
+    buffers: Vec<::gltf::buffer::Data>,
+    images: Vec<::gltf::image::Data>,
+    materials: Vec<Arc<dyn Material>>,
+    default_material: Arc<dyn Material>,
+    scene: GltfScene,
+}
+
+/// Loads the default scene of a `.gltf` or `.glb` file
+pub fn load(path: impl AsRef<Path>) -> ImportResult<GltfScene> {
+    let (document, buffers, images) =
+        ::gltf::import(path).map_err(|error| ImportError::Parse(error.to_string()))?;
+
+    let mut importer = Importer {
+        document,
+        buffers,
+        images,
+        materials: vec![],
+        default_material: Arc::new(Lambertian::new(Color::white() * 0.8)),
+        scene: GltfScene {
+            world: HitTarget::new(),
+            cameras: vec![],
+        },
+    };
+    importer.materials = importer
+        .document
+        .materials()
+        .map(|material| importer.material(&material))
+        .collect();
+
+    let document = importer.document.clone();
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| ImportError::Parse("glTF file has no scenes".into()))?;
+    for node in scene.nodes() {
+        importer.visit(&node, Transform::identity())?;
+    }
+    Ok(importer.scene)
+}
+
+fn to_f64<const N: usize>(values: [f32; N]) -> [f64; N] {
+    values.map(|value| value as f64)
+}
+
+impl Importer {
+    fn visit(&mut self, node: &Node, parent: Transform) -> ImportResult<()> {
+        let local = Transform::from_columns(node.transform().matrix().map(to_f64));
+        let transform = parent * local;
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != Mode::Triangles {
+                    continue;
+                }
+
+                let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+                let positions: Vec<Vector3> = reader
+                    .read_positions()
+                    .ok_or_else(|| ImportError::Parse("glTF primitive without positions".into()))?
+                    .map(|[x, y, z]| Vector3::new(x, y, z))
+                    .collect();
+                let indices: Vec<usize> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().map(|index| index as usize).collect(),
+                    None => (0..positions.len()).collect(),
+                };
+
+                let mut data = TriangleMesh::new(
+                    positions,
+                    indices
+                        .chunks_exact(3)
+                        .map(|t| [t[0], t[1], t[2]])
+                        .collect(),
+                );
+                if let Some(normals) = reader.read_normals() {
+                    data.normals = normals.map(|[x, y, z]| Vector3::new(x, y, z)).collect();
+                }
+                // ? glTF puts the uv origin at the top left of the image
+                if let Some(uvs) = reader.read_tex_coords(0) {
+                    data.uvs = uvs
+                        .into_f32()
+                        .map(|[u, v]| (u as f64, 1. - v as f64))
+                        .collect();
+                }
+                if let Some(tangents) = reader.read_tangents() {
+                    data.tangents = tangents
+                        .map(|[x, y, z, w]| (Vector3::new(x, y, z), w as f64))
+                        .collect();
+                }
+                if let Some(colors) = reader.read_colors(0) {
+                    data.colors = colors
+                        .into_rgb_f32()
+                        .map(|[r, g, b]| Color::new(r, g, b))
+                        .collect();
+                }
+
+                let material = primitive
+                    .material()
+                    .index()
+                    .map(|index| self.materials[index].clone())
+                    .unwrap_or_else(|| self.default_material.clone());
+                let data = data.transformed(&transform);
+                (*self.scene.world).push(Arc::new(Mesh::new(data, material)));
+            }
+        }
+
+        if let Some(camera) = node.camera() {
+            if let Projection::Perspective(perspective) = camera.projection() {
+                let position = transform.transform_point(&Vector3::zero());
+                let front = transform.transform_vector(&Vector3::new(0, 0, -1));
+                self.scene.cameras.push(GltfCamera {
+                    position,
+                    at: position + front,
+                    up: transform.transform_vector(&Vector3::up()),
+                    vfov: (perspective.yfov() as f64).to_degrees(),
+                    aspect_ratio: perspective.aspect_ratio().map(|ratio| ratio as f64),
+                });
+            }
+        }
+
+        for child in node.children() {
+            self.visit(&child, transform)?;
+        }
+        Ok(())
+    }
+
+    fn texture(&self, texture: &::gltf::Texture, srgb: bool) -> Option<ImageTexture> {
+        let image = &self.images[texture.source().index()];
+        let channels = match image.format {
+            Format::R8 => 1,
+            Format::R8G8 => 2,
+            Format::R8G8B8 => 3,
+            Format::R8G8B8A8 => 4,
+            _ => return None,
+        };
+
+        let decode = |value: u8| {
+            let value = value as f64 / 255.;
+            if srgb {
+                srgb_to_linear(value)
+            } else {
+                value
+            }
+        };
+        let texels = image
+            .pixels
+            .chunks_exact(channels)
+            .map(|texel| match channels {
+                1 | 2 => Color::ones() * decode(texel[0]),
+                _ => Color::new(decode(texel[0]), decode(texel[1]), decode(texel[2])),
+            })
+            .collect();
+        Some(ImageTexture::new(
+            image.width as usize,
+            image.height as usize,
+            texels,
+        ))
+    }
+
+    /// Alpha channel as a grey texture, opaque for images without one
+    fn alpha_texture(&self, texture: &::gltf::Texture) -> Option<ImageTexture> {
+        let image = &self.images[texture.source().index()];
+        let (channels, alpha) = match image.format {
+            Format::R8 | Format::R8G8B8 => return None,
+            Format::R8G8 => (2, 1),
+            Format::R8G8B8A8 => (4, 3),
+            _ => return None,
+        };
+        let texels = image
+            .pixels
+            .chunks_exact(channels)
+            .map(|texel| Color::ones() * (texel[alpha] as f64 / 255.))
+            .collect();
+        Some(ImageTexture::new(
+            image.width as usize,
+            image.height as usize,
+            texels,
+        ))
+    }
+
+    /// Maps metallic-roughness materials onto the principled BSDF
+    fn material(&self, material: &::gltf::Material) -> Arc<dyn Material> {
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, _] = to_f64(pbr.base_color_factor());
+        let base_color = Color::new(r, g, b);
+        let metallic = pbr.metallic_factor() as f64;
+        let roughness = pbr.roughness_factor() as f64;
+
+        let albedo: Arc<dyn Texture> = match pbr
+            .base_color_texture()
+            .and_then(|info| self.texture(&info.texture(), true))
+        {
+            Some(texture) => Arc::new(Tinted {
+                texture,
+                tint: base_color,
+            }),
+            None => Arc::new(SolidColor::new(base_color)),
+        };
+        let mut principled = Principled::textured(albedo)
+            .with_metallic(metallic)
+            .with_roughness(roughness);
+
+        // ? Roughness is stored in the green channel and metalness in the blue one
+        if let Some(texture) = pbr
+            .metallic_roughness_texture()
+            .and_then(|info| self.texture(&info.texture(), false))
+        {
+            let texture = Arc::new(texture);
+            principled = principled
+                .with_roughness_texture(Arc::new(Channel {
+                    texture: texture.clone(),
+                    channel: 1,
+                    factor: roughness,
+                }))
+                .with_metallic_texture(Arc::new(Channel {
+                    texture,
+                    channel: 2,
+                    factor: metallic,
+                }));
+        }
+        if let Some(transmission) = material.transmission() {
+            let index = material.ior().unwrap_or(1.5) as f64;
+            principled =
+                principled.with_transmission(transmission.transmission_factor() as f64, index);
+        }
+        let mut base: Arc<dyn Material> = Arc::new(principled);
+
+        if material.alpha_mode() == AlphaMode::Mask {
+            let [_, _, _, alpha] = to_f64(pbr.base_color_factor());
+            let opacity: Arc<dyn Texture> = match pbr
+                .base_color_texture()
+                .and_then(|info| self.alpha_texture(&info.texture()))
+            {
+                Some(texture) => Arc::new(Tinted {
+                    texture,
+                    tint: Color::ones() * alpha,
+                }),
+                None => Arc::new(SolidColor::new(Color::ones() * alpha)),
+            };
+            let cutoff = material.alpha_cutoff().unwrap_or(0.5) as f64;
+            base = Arc::new(Cutout::new(base, opacity, cutoff));
+        }
+
+        match material
+            .normal_texture()
+            .and_then(|normal| self.texture(&normal.texture(), false))
+        {
+            Some(normal_map) => Arc::new(NormalMapped::normal_map(base, Arc::new(normal_map))),
+            None => base,
+        }
+    }
+}
+
+/// Image texture multiplied by a constant factor
+struct Tinted {
+    texture: ImageTexture,
+    tint: Color,
+}
+
+impl Texture for Tinted {
+    fn value(&self, u: f64, v: f64, point: &Vector3) -> Color {
+        self.tint * self.texture.value(u, v, point)
+    }
+}
+
+/// Single image channel scaled by a factor, as a grey texture
+struct Channel {
+    texture: Arc<ImageTexture>,
+    channel: usize,
+    factor: f64,
+}
+
+impl Texture for Channel {
+    fn value(&self, u: f64, v: f64, point: &Vector3) -> Color {
+        Color::white() * self.factor * self.texture.value(u, v, point)[self.channel]
+    }
+}
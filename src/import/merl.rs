@@ -0,0 +1,89 @@
+use std::{fs, path::Path};
+
+use crate::object::material::{
+    color::Color,
+    measured::{MeasuredBrdf, MERL_RESOLUTION},
+};
+
+use super::{ImportError, ImportResult};
+
+/// Scale from stored values to reflectance per channel
+const CHANNEL_SCALE: [f64; 3] = [1. / 1500., 1.15 / 1500., 1.66 / 1500.];
+
+pub fn load(path: impl AsRef<Path>) -> ImportResult<MeasuredBrdf> {
+    parse(&fs::read(path)?)
+}
+
+/// Parses a MERL `.binary` file: three `i32` dimensions followed by one
+/// block of little endian `f64` values per color channel
+pub fn parse(bytes: &[u8]) -> ImportResult<MeasuredBrdf> {
+    if bytes.len() < 12 {
+        return Err(ImportError::Parse("MERL file is missing its header".into()));
+    }
+    let dimensions: Vec<usize> = (0..3)
+        .map(|i| i32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap()) as usize)
+        .collect();
+    if dimensions != MERL_RESOLUTION {
+        return Err(ImportError::Unsupported(format!(
+            "MERL resolution {dimensions:?}"
+        )));
+    }
+
+    let count: usize = MERL_RESOLUTION.iter().product();
+    if bytes.len() < 12 + 3 * 8 * count {
+        return Err(ImportError::Parse(format!(
+            "MERL file is only {} bytes",
+            bytes.len()
+        )));
+    }
+    let value = |channel: usize, i: usize| {
+        let start = 12 + 8 * (channel * count + i);
+        f64::from_le_bytes(bytes[start..start + 8].try_into().unwrap()) * CHANNEL_SCALE[channel]
+    };
+
+    let values = (0..count)
+        .map(|i| Color::new(value(0, i), value(1, i), value(2, i)))
+        .collect();
+    Ok(MeasuredBrdf::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::geometry::vector::Vector3;
+
+    fn file(dimensions: [i32; 3], values: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = dimensions.iter().flat_map(|d| d.to_le_bytes()).collect();
+        for i in 0..values {
+            bytes.extend_from_slice(&(i as f64).to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn scales_each_channel() {
+        let count: usize = MERL_RESOLUTION.iter().product();
+        let brdf = parse(&file([90, 90, 180], 3 * count)).unwrap();
+        // ? Normal incidence reads the first entry of each channel block
+        let up = Vector3::new(0, 0, 1);
+        let value = brdf.eval_local(&up, &up);
+        let expected = [0., count as f64, 2. * count as f64];
+        for channel in 0..3 {
+            let scaled = expected[channel] * CHANNEL_SCALE[channel];
+            assert!((value[channel] - scaled).abs() < 1e-9 * scaled.max(1.));
+        }
+    }
+
+    #[test]
+    fn rejects_other_resolutions_and_truncated_files() {
+        assert!(matches!(
+            parse(&file([90, 90, 360], 0)),
+            Err(ImportError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse(&file([90, 90, 180], 100)),
+            Err(ImportError::Parse(_))
+        ));
+        assert!(matches!(parse(&[0; 8]), Err(ImportError::Parse(_))));
+    }
+}
@@ -0,0 +1,1017 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    object::{
+        geometry::{
+            mesh::{Mesh, TriangleMesh},
+            sphere::Sphere,
+            transform::Transform,
+            vector::Vector3,
+        },
+        light::{
+            Background, DirectionalLight, Light, MeshLight, PointLight, SphereLight, SpotLight,
+        },
+        material::{
+            color::Color, conductor::Conductor, dielectric::Dielectric, lambertian::Lambertian,
+            metal::Metal, oren_nayar::OrenNayar, rough_dielectric::RoughDielectric, Material,
+        },
+    },
+    render::integrator::DEFAULT_MIN_DEPTH,
+    view::{camera::Camera, ray::HitTarget},
+};
+
+use super::{ply, ImportError, ImportResult};
+
+/// Scene described by a pbrt-v3 file, limited to the supported subset
+pub struct PbrtScene {
+    pub world: HitTarget,
+    pub camera: Camera,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub samples: u32,
+    pub max_depth: u32,
+    /// Bounces traced before Russian roulette, from the integrator's
+    /// `mindepth`, which pbrt-v3 does not have
+    pub min_depth: u32,
+    pub filename: String,
+    pub lights: Vec<Arc<dyn Light>>,
+    /// Radiance of the `infinite` light, black if the file declares none
+    pub background: Background,
+    /// Closest [`integrator`](crate::render::integrator) to the requested one
+    pub integrator: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Number(f64),
+    Open,
+    Close,
+    /// Marks where the tokens of an `Include`d file end
+    EndInclude,
+}
+
+/// Nesting of `Include` directives beyond which parsing fails
+const MAX_INCLUDE_DEPTH: usize = 32;
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Params {
+    values: HashMap<String, (String, Vec<Value>)>,
+}
+
+impl Params {
+    fn numbers(&self, name: &str) -> Option<Vec<f64>> {
+        let (_, values) = self.values.get(name)?;
+        Some(
+            values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Number(number) => Some(*number),
+                    Value::Str(_) => None,
+                })
+                .collect(),
+        )
+    }
+
+    fn number(&self, name: &str, default: f64) -> f64 {
+        self.numbers(name)
+            .and_then(|numbers| numbers.first().copied())
+            .unwrap_or(default)
+    }
+
+    fn string(&self, name: &str) -> Option<String> {
+        match self.values.get(name)?.1.first()? {
+            Value::Str(string) => Some(string.clone()),
+            Value::Number(_) => None,
+        }
+    }
+
+    fn boolean(&self, name: &str, default: bool) -> bool {
+        self.string(name)
+            .map(|value| value == "true")
+            .unwrap_or(default)
+    }
+
+    /// Only `rgb` spectra are understood, anything else uses the default
+    fn color(&self, name: &str, default: Color) -> Color {
+        match self.values.get(name) {
+            Some((ty, _)) if ty == "rgb" || ty == "color" => match self.numbers(name) {
+                Some(rgb) if rgb.len() == 3 => Color::new(rgb[0], rgb[1], rgb[2]),
+                _ => default,
+            },
+            _ => default,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    transform: Transform,
+    material: Arc<dyn Material>,
+    area_light: Option<AreaLight>,
+}
+
+/// Emission given to the shapes that follow an `AreaLightSource`
+#[derive(Clone, Copy)]
+struct AreaLight {
+    emit: Color,
+    two_sided: bool,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    directory: PathBuf,
+    /// Files whose `Include` is being parsed, innermost last
+    includes: Vec<PathBuf>,
+    state: GraphicsState,
+    stack: Vec<GraphicsState>,
+    named_materials: HashMap<String, Arc<dyn Material>>,
+    named_coordinate_systems: HashMap<String, Transform>,
+    camera_params: Params,
+    camera_from_world: Transform,
+    film: Params,
+    sampler: Params,
+    integrator_name: String,
+    integrator: Params,
+    shapes: Vec<(Transform, Shape, Option<AreaLight>)>,
+    lights: Vec<(Transform, LightSource)>,
+    background: Background,
+}
+
+enum Shape {
+    Sphere(f64, Arc<dyn Material>),
+    Mesh(TriangleMesh, Arc<dyn Material>),
+}
+
+/// Light with its parameters in the space it was declared in
+enum LightSource {
+    Point {
+        from: Vector3,
+        intensity: Color,
+    },
+    Spot {
+        from: Vector3,
+        to: Vector3,
+        intensity: Color,
+        cone_angle: f64,
+        cone_delta: f64,
+    },
+    Distant {
+        from: Vector3,
+        to: Vector3,
+        radiance: Color,
+    },
+}
+
+pub fn load(path: impl AsRef<Path>) -> ImportResult<PbrtScene> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    parse(&text, directory)
+}
+
+/// Parses pbrt-v3 scene text, relative file names are resolved against `directory`
+pub fn parse(text: &str, directory: impl Into<PathBuf>) -> ImportResult<PbrtScene> {
+    let mut parser = Parser::new(tokenize(text)?, directory.into());
+    parser.run()?;
+    Ok(parser.scene())
+}
+
+fn tokenize(text: &str) -> ImportResult<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '[' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ']' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err(ImportError::Parse("unterminated string".into())),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) =
+                    chars.next_if(|&c| !c.is_whitespace() && !matches!(c, '[' | ']' | '"' | '#'))
+                {
+                    word.push(c);
+                }
+                tokens.push(match word.parse() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => Token::Word(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn matrix(numbers: &[f64]) -> ImportResult<Transform> {
+    if numbers.len() != 16 {
+        return Err(ImportError::Parse(format!(
+            "expected 16 matrix values, found {}",
+            numbers.len()
+        )));
+    }
+    let mut columns = [[0.; 4]; 4];
+    for (i, column) in columns.iter_mut().enumerate() {
+        column.copy_from_slice(&numbers[4 * i..4 * i + 4]);
+    }
+    Ok(Transform::from_columns(columns))
+}
+
+/// pbrt's `LookAt`, which returns the camera from world transform
+fn look_at(position: Vector3, at: Vector3, up: Vector3) -> ImportResult<Transform> {
+    let direction = (at - position).normalize();
+    let right = Vector3::cross(&up.normalize(), &direction);
+    if right.is_near_zero() {
+        return Err(ImportError::Parse(
+            "LookAt up vector is parallel to the view".into(),
+        ));
+    }
+    let right = right.normalize();
+    let up = Vector3::cross(&direction, &right);
+
+    let world_from_camera = Transform::from_columns([
+        [right.x(), right.y(), right.z(), 0.],
+        [up.x(), up.y(), up.z(), 0.],
+        [direction.x(), direction.y(), direction.z(), 0.],
+        [position.x(), position.y(), position.z(), 1.],
+    ]);
+    world_from_camera
+        .inverse()
+        .ok_or_else(|| ImportError::Parse("singular LookAt".into()))
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>, directory: PathBuf) -> Self {
+        let default_material: Arc<dyn Material> = Arc::new(Lambertian::new(Color::white() / 2));
+        Self {
+            tokens,
+            position: 0,
+            directory,
+            includes: vec![],
+            state: GraphicsState {
+                transform: Transform::identity(),
+                material: default_material,
+                area_light: None,
+            },
+            stack: vec![],
+            named_materials: HashMap::new(),
+            named_coordinate_systems: HashMap::new(),
+            camera_params: Params::default(),
+            camera_from_world: Transform::identity(),
+            film: Params::default(),
+            sampler: Params::default(),
+            integrator_name: "path".into(),
+            integrator: Params::default(),
+            shapes: vec![],
+            lights: vec![],
+            background: Background::Uniform(Color::black()),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn number(&mut self) -> ImportResult<f64> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(number),
+            token => Err(ImportError::Parse(format!(
+                "expected a number, found {token:?}"
+            ))),
+        }
+    }
+
+    fn numbers<const N: usize>(&mut self) -> ImportResult<[f64; N]> {
+        let mut numbers = [0.; N];
+        for number in numbers.iter_mut() {
+            *number = self.number()?;
+        }
+        Ok(numbers)
+    }
+
+    fn string(&mut self) -> ImportResult<String> {
+        match self.next() {
+            Some(Token::Str(string)) => Ok(string),
+            token => Err(ImportError::Parse(format!(
+                "expected a string, found {token:?}"
+            ))),
+        }
+    }
+
+    /// Bracketed or bare list of numbers
+    fn number_list(&mut self) -> ImportResult<Vec<f64>> {
+        if self.peek() != Some(&Token::Open) {
+            return Ok(vec![self.number()?]);
+        }
+        self.next();
+        let mut numbers = vec![];
+        while self.peek() != Some(&Token::Close) {
+            numbers.push(self.number()?);
+        }
+        self.next();
+        Ok(numbers)
+    }
+
+    fn params(&mut self) -> ImportResult<Params> {
+        let mut params = Params::default();
+        while let Some(Token::Str(declaration)) = self.peek().cloned() {
+            self.next();
+            let mut words = declaration.split_whitespace();
+            let (Some(ty), Some(name)) = (words.next(), words.next()) else {
+                return Err(ImportError::Parse(format!(
+                    "malformed parameter `{declaration}`"
+                )));
+            };
+
+            let mut values = vec![];
+            let bracketed = self.peek() == Some(&Token::Open);
+            if bracketed {
+                self.next();
+            }
+            loop {
+                match self.peek() {
+                    Some(Token::Number(number)) => values.push(Value::Number(*number)),
+                    Some(Token::Str(string)) if bracketed => {
+                        values.push(Value::Str(string.clone()))
+                    }
+                    Some(Token::Str(string)) if values.is_empty() => {
+                        values.push(Value::Str(string.clone()))
+                    }
+                    Some(Token::Word(word)) if bracketed => values.push(Value::Str(word.clone())),
+                    Some(Token::Close) if bracketed => {
+                        self.next();
+                        break;
+                    }
+                    _ => break,
+                }
+                self.next();
+                if !bracketed {
+                    break;
+                }
+            }
+            params
+                .values
+                .insert(name.to_string(), (ty.to_string(), values));
+        }
+        Ok(params)
+    }
+
+    fn run(&mut self) -> ImportResult<()> {
+        while let Some(token) = self.next() {
+            if token == Token::EndInclude {
+                self.includes.pop();
+                continue;
+            }
+            let Token::Word(directive) = token else {
+                return Err(ImportError::Parse(format!(
+                    "expected a directive, found {token:?}"
+                )));
+            };
+
+            match directive.as_str() {
+                "LookAt" => {
+                    let [px, py, pz, ax, ay, az, ux, uy, uz] = self.numbers()?;
+                    let look_at = look_at(
+                        Vector3::new(px, py, pz),
+                        Vector3::new(ax, ay, az),
+                        Vector3::new(ux, uy, uz),
+                    )?;
+                    self.state.transform = self.state.transform * look_at;
+                }
+                "Translate" => {
+                    let [x, y, z] = self.numbers()?;
+                    self.state.transform =
+                        self.state.transform * Transform::translate(Vector3::new(x, y, z));
+                }
+                "Scale" => {
+                    let [x, y, z] = self.numbers()?;
+                    self.state.transform =
+                        self.state.transform * Transform::scale(Vector3::new(x, y, z));
+                }
+                "Rotate" => {
+                    let [angle, x, y, z] = self.numbers()?;
+                    self.state.transform =
+                        self.state.transform * Transform::rotate(angle, Vector3::new(x, y, z));
+                }
+                "Identity" => self.state.transform = Transform::identity(),
+                "Transform" => self.state.transform = matrix(&self.number_list()?)?,
+                "ConcatTransform" => {
+                    self.state.transform = self.state.transform * matrix(&self.number_list()?)?
+                }
+                "CoordinateSystem" => {
+                    let name = self.string()?;
+                    self.named_coordinate_systems
+                        .insert(name, self.state.transform);
+                }
+                "CoordSysTransform" => {
+                    let name = self.string()?;
+                    if let Some(transform) = self.named_coordinate_systems.get(&name) {
+                        self.state.transform = *transform;
+                    }
+                }
+                "Camera" => {
+                    let ty = self.string()?;
+                    if ty != "perspective" {
+                        return Err(ImportError::Unsupported(format!("camera `{ty}`")));
+                    }
+                    self.camera_params = self.params()?;
+                    self.camera_from_world = self.state.transform;
+                    self.named_coordinate_systems.insert(
+                        "camera".into(),
+                        self.state.transform.inverse().unwrap_or_default(),
+                    );
+                }
+                "Film" => {
+                    self.string()?;
+                    self.film = self.params()?;
+                }
+                "Sampler" => {
+                    self.string()?;
+                    self.sampler = self.params()?;
+                }
+                "Integrator" => {
+                    self.integrator_name = self.string()?;
+                    self.integrator = self.params()?;
+                }
+                "WorldBegin" => self.state.transform = Transform::identity(),
+                "WorldEnd" => {}
+                "AttributeBegin" | "TransformBegin" => self.stack.push(self.state.clone()),
+                "AttributeEnd" => {
+                    self.state = self
+                        .stack
+                        .pop()
+                        .ok_or_else(|| ImportError::Parse("unmatched AttributeEnd".into()))?;
+                }
+                "TransformEnd" => {
+                    let saved = self
+                        .stack
+                        .pop()
+                        .ok_or_else(|| ImportError::Parse("unmatched TransformEnd".into()))?;
+                    self.state.transform = saved.transform;
+                }
+                "Material" => {
+                    let ty = self.string()?;
+                    let params = self.params()?;
+                    self.state.material = material(&ty, &params)?;
+                }
+                "MakeNamedMaterial" => {
+                    let name = self.string()?;
+                    let params = self.params()?;
+                    let ty = params.string("type").unwrap_or_default();
+                    self.named_materials.insert(name, material(&ty, &params)?);
+                }
+                "NamedMaterial" => {
+                    let name = self.string()?;
+                    self.state.material =
+                        self.named_materials.get(&name).cloned().ok_or_else(|| {
+                            ImportError::Parse(format!("unknown material `{name}`"))
+                        })?;
+                }
+                "AreaLightSource" => {
+                    self.string()?;
+                    let params = self.params()?;
+                    self.state.area_light = Some(AreaLight {
+                        emit: params.color("L", Color::white()) * params.number("scale", 1.),
+                        two_sided: params.boolean("twosided", false),
+                    });
+                }
+                "LightSource" => {
+                    let ty = self.string()?;
+                    let params = self.params()?;
+                    self.light(&ty, &params)?;
+                }
+                "Shape" => {
+                    let ty = self.string()?;
+                    let params = self.params()?;
+                    self.shape(&ty, &params)?;
+                }
+                "Include" => {
+                    let file = self.string()?;
+                    let path = fs::canonicalize(self.directory.join(file))?;
+                    if self.includes.contains(&path) {
+                        return Err(ImportError::Parse(format!(
+                            "`{}` includes itself",
+                            path.display()
+                        )));
+                    }
+                    if self.includes.len() >= MAX_INCLUDE_DEPTH {
+                        return Err(ImportError::Parse("Include nested too deeply".into()));
+                    }
+                    let mut included = tokenize(&fs::read_to_string(&path)?)?;
+                    included.push(Token::EndInclude);
+                    self.tokens.splice(self.position..self.position, included);
+                    self.includes.push(path);
+                }
+                // ? Directives outside the supported subset are skipped with
+                // their arguments
+                _ => {
+                    while let Some(Token::Str(_)) = self.peek() {
+                        self.next();
+                    }
+                    self.params()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn shape(&mut self, ty: &str, params: &Params) -> ImportResult<()> {
+        let material = self.state.material.clone();
+
+        let shape = match ty {
+            "sphere" => Shape::Sphere(params.number("radius", 1.), material),
+            "trianglemesh" => {
+                let indices = params
+                    .numbers("indices")
+                    .ok_or_else(|| ImportError::Parse("trianglemesh without indices".into()))?;
+                let positions = params
+                    .numbers("P")
+                    .ok_or_else(|| ImportError::Parse("trianglemesh without P".into()))?;
+
+                let mut mesh = TriangleMesh::new(
+                    positions
+                        .chunks_exact(3)
+                        .map(|p| Vector3::new(p[0], p[1], p[2]))
+                        .collect(),
+                    indices
+                        .chunks_exact(3)
+                        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+                        .collect(),
+                );
+                if let Some(normals) = params.numbers("N") {
+                    mesh.normals = normals
+                        .chunks_exact(3)
+                        .map(|n| Vector3::new(n[0], n[1], n[2]))
+                        .collect();
+                }
+                if let Some(uvs) = params.numbers("uv").or_else(|| params.numbers("st")) {
+                    mesh.uvs = uvs.chunks_exact(2).map(|uv| (uv[0], uv[1])).collect();
+                }
+                if mesh
+                    .triangles
+                    .iter()
+                    .flatten()
+                    .any(|&v| v >= mesh.positions.len())
+                {
+                    return Err(ImportError::Parse("trianglemesh index out of range".into()));
+                }
+                Shape::Mesh(mesh, material)
+            }
+            "plymesh" => {
+                let filename = params
+                    .string("filename")
+                    .ok_or_else(|| ImportError::Parse("plymesh without filename".into()))?;
+                Shape::Mesh(ply::load(self.directory.join(filename))?, material)
+            }
+            _ => return Err(ImportError::Unsupported(format!("shape `{ty}`"))),
+        };
+        self.shapes
+            .push((self.state.transform, shape, self.state.area_light));
+        Ok(())
+    }
+
+    fn light(&mut self, ty: &str, params: &Params) -> ImportResult<()> {
+        let point = |name: &str, default: Vector3| match params.numbers(name) {
+            Some(xyz) if xyz.len() == 3 => Vector3::new(xyz[0], xyz[1], xyz[2]),
+            _ => default,
+        };
+        let scale = params.number("scale", 1.);
+        let light = match ty {
+            "point" => LightSource::Point {
+                from: point("from", Vector3::zero()),
+                intensity: params.color("I", Color::white()) * scale,
+            },
+            "spot" => LightSource::Spot {
+                from: point("from", Vector3::zero()),
+                to: point("to", Vector3::new(0, 0, 1)),
+                intensity: params.color("I", Color::white()) * scale,
+                cone_angle: params.number("coneangle", 30.),
+                cone_delta: params.number("conedelta", 5.),
+            },
+            "distant" => LightSource::Distant {
+                from: point("from", Vector3::zero()),
+                to: point("to", Vector3::new(0, 0, 1)),
+                radiance: params.color("L", Color::white()) * scale,
+            },
+            // ? Only uniform environments are supported, they become the
+            // background rather than a light
+            "infinite" if params.string("mapname").is_none() => {
+                self.background = Background::Uniform(params.color("L", Color::white()) * scale);
+                return Ok(());
+            }
+            _ => return Err(ImportError::Unsupported(format!("light `{ty}`"))),
+        };
+        self.lights.push((self.state.transform, light));
+        Ok(())
+    }
+
+    fn scene(self) -> PbrtScene {
+        let image_width = self.film.number("xresolution", 640.) as u32;
+        let image_height = self.film.number("yresolution", 480.) as u32;
+        let aspect_ratio = image_width as f64 / image_height as f64;
+
+        // ? pbrt's fov spans the shorter image axis
+        let fov = self.camera_params.number("fov", 90.);
+        let vfov = if aspect_ratio >= 1. {
+            fov
+        } else {
+            2. * ((fov.to_radians() / 2.).tan() / aspect_ratio)
+                .atan()
+                .to_degrees()
+        };
+        let lens_radius = self.camera_params.number("lensradius", 0.);
+        let focus_distance = if lens_radius > 0. {
+            self.camera_params.number("focaldistance", 1e6)
+        } else {
+            1.
+        };
+
+        let world_from_camera = self.camera_from_world.inverse().unwrap_or_default();
+        let position = world_from_camera.transform_point(&Vector3::zero());
+        let direction = world_from_camera.transform_vector(&Vector3::new(0, 0, 1));
+        let up = world_from_camera.transform_vector(&Vector3::up());
+        let right = world_from_camera
+            .transform_vector(&Vector3::new(1, 0, 0))
+            .normalize();
+        let camera = Camera::new(
+            position,
+            position + direction,
+            up,
+            vfov,
+            aspect_ratio,
+            2. * lens_radius,
+            focus_distance,
+        );
+
+        // ? pbrt's camera space is left-handed, so the world is mirrored across
+        // the camera's vertical plane to keep images the same way around
+        let mut reflection = *Transform::identity().matrix();
+        for i in 0..3 {
+            for j in 0..3 {
+                reflection[i][j] -= 2. * right[i] * right[j];
+            }
+        }
+        let mirror = Transform::translate(position)
+            * Transform::new(reflection)
+            * Transform::translate(-position);
+
+        // ? Emissive shapes are added through their light, so the light
+        // samples the same surface the rays hit
+        let mut world = HitTarget::new();
+        let mut area_lights: Vec<Arc<dyn Light>> = vec![];
+        for (transform, shape, area_light) in self.shapes {
+            let transform = mirror * transform;
+            match (shape, area_light) {
+                (Shape::Sphere(radius, material), area_light) => {
+                    let center = transform.transform_point(&Vector3::zero());
+                    let radius = radius * transform.determinant().abs().cbrt();
+                    match area_light {
+                        Some(AreaLight { emit, .. }) => {
+                            let light = SphereLight::new(center, radius, emit);
+                            (*world).push(Arc::new(light.sphere()));
+                            area_lights.push(Arc::new(light));
+                        }
+                        None => (*world).push(Arc::new(Sphere::new(center, radius, material))),
+                    }
+                }
+                (Shape::Mesh(mesh, _), Some(AreaLight { emit, two_sided })) => {
+                    let light = MeshLight::new(mesh.transformed(&transform), emit, two_sided);
+                    (*world).push(light.mesh());
+                    area_lights.push(Arc::new(light));
+                }
+                (Shape::Mesh(mesh, material), None) => {
+                    (*world).push(Arc::new(Mesh::new(mesh.transformed(&transform), material)));
+                }
+            }
+        }
+
+        let lights = self
+            .lights
+            .into_iter()
+            .map(|(transform, light)| -> Arc<dyn Light> {
+                let transform = mirror * transform;
+                match light {
+                    LightSource::Point { from, intensity } => {
+                        Arc::new(PointLight::new(transform.transform_point(&from), intensity))
+                    }
+                    LightSource::Spot {
+                        from,
+                        to,
+                        intensity,
+                        cone_angle,
+                        cone_delta,
+                    } => Arc::new(SpotLight::new(
+                        transform.transform_point(&from),
+                        transform.transform_point(&to),
+                        intensity,
+                        cone_angle - cone_delta,
+                        cone_angle,
+                    )),
+                    LightSource::Distant { from, to, radiance } => Arc::new(DirectionalLight::new(
+                        transform.transform_vector(&(from - to)),
+                        radiance,
+                        0.,
+                    )),
+                }
+            })
+            .chain(area_lights)
+            .collect();
+
+        PbrtScene {
+            world,
+            camera,
+            image_width,
+            image_height,
+            samples: self.sampler.number("pixelsamples", 16.) as u32,
+            max_depth: self.integrator.number("maxdepth", 5.) as u32,
+            min_depth: self.integrator.number("mindepth", DEFAULT_MIN_DEPTH as f64) as u32,
+            filename: self
+                .film
+                .string("filename")
+                .unwrap_or_else(|| "pbrt.png".into()),
+            lights,
+            background: self.background,
+            integrator: integrator(&self.integrator_name).into(),
+        }
+    }
+}
+
+/// Maps pbrt integrator names to ours, defaulting to next event estimation
+fn integrator(name: &str) -> &'static str {
+    match name {
+        "ambientocclusion" => "ao",
+        "whitted" => "whitted",
+        "directlighting" => "direct",
+        "randomwalk" => "path",
+        "bdpt" => "bdpt",
+        _ => "nee",
+    }
+}
+
+/// pbrt-v3's `RoughnessToAlpha`, a fit on the logarithm of the roughness
+fn roughness_to_alpha(roughness: f64) -> f64 {
+    let x = roughness.max(1e-3).ln();
+    1.62142 + 0.819955 * x + 0.1734 * x * x + 0.0171201 * x.powi(3) + 0.000640711 * x.powi(4)
+}
+
+/// Reads `uroughness` and `vroughness` as perceptual roughness
+fn roughness(params: &Params, default: f64) -> (f64, f64) {
+    let roughness = params.number("roughness", default);
+    // ? pbrt-v3 remaps roughness to alpha unless told not to, while the
+    // microfacet materials square their perceptual roughness into alpha
+    let to_perceptual = |r: f64| {
+        if r <= 0. {
+            0.
+        } else if params.boolean("remaproughness", true) {
+            roughness_to_alpha(r).max(0.).sqrt()
+        } else {
+            r.sqrt()
+        }
+    };
+    (
+        to_perceptual(params.number("uroughness", roughness)),
+        to_perceptual(params.number("vroughness", roughness)),
+    )
+}
+
+fn material(ty: &str, params: &Params) -> ImportResult<Arc<dyn Material>> {
+    let material: Arc<dyn Material> = match ty {
+        "metal" | "conductor" => {
+            // ? Defaults to copper like pbrt
+            let eta = params.color("eta", Color::new(0.2, 1.1, 1.2));
+            let k = params.color("k", Color::new(3.9, 2.6, 2.3));
+            Arc::new(Conductor::new(eta, k, roughness(params, 0.01)))
+        }
+        "mirror" => Arc::new(Metal::new(params.color("Kr", Color::white() * 0.9), 0.)),
+        "glass" | "dielectric" => {
+            let index = params.number("index", params.number("eta", 1.5));
+            let (u, v) = roughness(params, 0.);
+            if u > 0. || v > 0. {
+                Arc::new(RoughDielectric::new(index, (u, v)))
+            } else {
+                Arc::new(Dielectric::new(index))
+            }
+        }
+        "matte" => {
+            let albedo = params.color("Kd", Color::white() / 2);
+            match params.number("sigma", 0.) {
+                sigma if sigma > 0. => Arc::new(OrenNayar::new(albedo, sigma)),
+                _ => Arc::new(Lambertian::new(albedo)),
+            }
+        }
+        _ => return Err(ImportError::Unsupported(format!("material `{ty}`"))),
+    };
+    Ok(material)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(text: &str) -> Params {
+        Parser::new(tokenize(text).unwrap(), PathBuf::new())
+            .params()
+            .unwrap()
+    }
+
+    #[test]
+    fn tokenizes_words_strings_numbers_and_brackets() {
+        let tokens = tokenize("Shape \"sphere\" # comment \"ignored\"\n[ 1 -2.5e1 ]").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Word("Shape".into()),
+                Token::Str("sphere".into()),
+                Token::Open,
+                Token::Number(1.),
+                Token::Number(-25.),
+                Token::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_adjacent_brackets_and_strings() {
+        let tokens = tokenize("[\"a b\"]\"c\"").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Open,
+                Token::Str("a b".into()),
+                Token::Close,
+                Token::Str("c".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_string() {
+        assert!(matches!(
+            tokenize("Shape \"sphere"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn parses_parameter_lists() {
+        let params = params(
+            "\"float radius\" 2 \"rgb Kd\" [0.1 0.2 0.3] \"string filename\" \"out.png\" \
+             \"bool twosided\" \"true\" \"integer indices\" [0 1 2 2 3 0]",
+        );
+        assert_eq!(params.number("radius", 1.), 2.);
+        assert_eq!(
+            params.color("Kd", Color::black()).to_array(),
+            [0.1, 0.2, 0.3]
+        );
+        assert_eq!(params.string("filename").as_deref(), Some("out.png"));
+        assert!(params.boolean("twosided", false));
+        assert_eq!(params.numbers("indices").unwrap().len(), 6);
+        assert_eq!(params.number("missing", 7.), 7.);
+    }
+
+    #[test]
+    fn ignores_non_rgb_spectra() {
+        let params = params("\"blackbody L\" [6500 1]");
+        assert_eq!(params.color("L", Color::white()).to_array(), [1.; 3]);
+    }
+
+    #[test]
+    fn params_stop_at_the_next_directive() {
+        let mut parser = Parser::new(
+            tokenize("\"float radius\" 2 Shape").unwrap(),
+            PathBuf::new(),
+        );
+        parser.params().unwrap();
+        assert_eq!(parser.next(), Some(Token::Word("Shape".into())));
+    }
+
+    #[test]
+    fn remaps_roughness_like_pbrt_v3() {
+        assert!((roughness_to_alpha(0.01) - 0.138924).abs() < 1e-5);
+        let params = params("\"float roughness\" 0.01");
+        let (u, v) = roughness(&params, 0.);
+        assert!((u * u - 0.138924).abs() < 1e-5);
+        assert_eq!(u, v);
+    }
+
+    #[test]
+    fn parses_a_small_scene() {
+        let scene = parse(
+            "LookAt 0 0 5  0 0 0  0 1 0
+             Camera \"perspective\" \"float fov\" 45
+             Film \"image\" \"integer xresolution\" 64 \"integer yresolution\" 32
+             Integrator \"directlighting\" \"integer maxdepth\" 3 \"integer mindepth\" 1
+             WorldBegin
+             LightSource \"point\" \"rgb I\" [1 1 1]
+             AttributeBegin
+               Material \"matte\" \"rgb Kd\" [0.5 0.5 0.5]
+               Shape \"sphere\" \"float radius\" 1
+             AttributeEnd
+             WorldEnd",
+            PathBuf::new(),
+        )
+        .unwrap();
+        assert_eq!((scene.image_width, scene.image_height), (64, 32));
+        assert_eq!(scene.max_depth, 3);
+        assert_eq!(scene.min_depth, 1);
+        assert_eq!(scene.integrator, "direct");
+        assert_eq!(scene.lights.len(), 1);
+        assert_eq!(scene.world.len(), 1);
+    }
+
+    #[test]
+    fn reads_uniform_infinite_lights_as_the_background() {
+        let background = |text: &str| match parse(text, PathBuf::new()).unwrap().background {
+            Background::Uniform(color) => color.to_array(),
+            Background::Sky => panic!("pbrt scenes have no sky"),
+        };
+        assert_eq!(background("WorldBegin WorldEnd"), [0., 0., 0.]);
+        assert_eq!(
+            background("LightSource \"infinite\" \"rgb L\" [0.1 0.2 0.3] \"float scale\" 2"),
+            [0.2, 0.4, 0.6]
+        );
+        assert!(matches!(
+            parse(
+                "LightSource \"infinite\" \"string mapname\" \"sky.exr\"",
+                PathBuf::new()
+            ),
+            Err(ImportError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn turns_area_lights_into_lights() {
+        let scene = parse(
+            "WorldBegin
+             AttributeBegin
+               AreaLightSource \"diffuse\" \"rgb L\" [4 4 4]
+               Shape \"sphere\" \"float radius\" 0.5
+               Shape \"trianglemesh\" \"integer indices\" [0 1 2]
+                 \"point P\" [0 0 0  1 0 0  0 1 0]
+             AttributeEnd
+             Shape \"sphere\"
+             WorldEnd",
+            PathBuf::new(),
+        )
+        .unwrap();
+        assert_eq!(scene.lights.len(), 2);
+        assert_eq!(scene.world.len(), 3);
+    }
+
+    #[test]
+    fn rejects_unknown_materials() {
+        assert!(matches!(
+            parse("Material \"plastic\" \"rgb Kd\" [1 0 0]", PathBuf::new()),
+            Err(ImportError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_self_include() {
+        let directory = std::env::temp_dir().join(format!("pbrt-include-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("loop.pbrt"), "Include \"loop.pbrt\"\n").unwrap();
+        let result = parse("Include \"loop.pbrt\"", &directory);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(matches!(result, Err(ImportError::Parse(_))));
+    }
+}
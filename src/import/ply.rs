@@ -0,0 +1,478 @@
+use std::{fs, path::Path};
+
+use crate::object::{
+    geometry::{mesh::TriangleMesh, vector::Vector3},
+    material::color::Color,
+    texture::srgb_to_linear,
+};
+
+use super::{ImportError, ImportResult};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> ImportResult<Self> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => return Err(ImportError::Parse(format!("unknown PLY type `{name}`"))),
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// Linear color channel, integer colors are sRGB encoded like 8-bit images
+    fn color(&self, value: f64) -> f64 {
+        match self {
+            ScalarType::U8 => srgb_to_linear(value / 255.),
+            ScalarType::U16 => srgb_to_linear(value / 65535.),
+            _ => value,
+        }
+    }
+}
+
+enum Property {
+    Scalar {
+        name: String,
+        ty: ScalarType,
+    },
+    List {
+        name: String,
+        count: ScalarType,
+        item: ScalarType,
+    },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads scalars from the body in either ascii or binary encoding
+struct Body<'a> {
+    format: Format,
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: ScalarType) -> ImportResult<f64> {
+        match self.format {
+            Format::Ascii => self.read_ascii(),
+            _ => self.read_binary(ty),
+        }
+    }
+
+    /// Length of a list, checked against what is left of the body so that
+    /// corrupt counts fail instead of allocating
+    fn read_count(&mut self, count: ScalarType, item: ScalarType) -> ImportResult<usize> {
+        let value = self.read(count)?;
+        // ? Every ascii item takes at least a digit and a separator
+        let item_size = match self.format {
+            Format::Ascii => 2,
+            _ => item.size(),
+        };
+        let remaining = self.bytes.len() - self.offset;
+        if !(0. ..=(remaining / item_size + 1) as f64).contains(&value) {
+            return Err(ImportError::Parse(format!(
+                "invalid PLY list length {value}"
+            )));
+        }
+        Ok(value as usize)
+    }
+
+    fn read_ascii(&mut self) -> ImportResult<f64> {
+        while self.offset < self.bytes.len() && self.bytes[self.offset].is_ascii_whitespace() {
+            self.offset += 1;
+        }
+        let start = self.offset;
+        while self.offset < self.bytes.len() && !self.bytes[self.offset].is_ascii_whitespace() {
+            self.offset += 1;
+        }
+
+        let token = std::str::from_utf8(&self.bytes[start..self.offset])
+            .map_err(|_| ImportError::Parse("invalid ascii PLY body".into()))?;
+        token
+            .parse()
+            .map_err(|_| ImportError::Parse(format!("expected a number, found `{token}`")))
+    }
+
+    fn read_binary(&mut self, ty: ScalarType) -> ImportResult<f64> {
+        let size = ty.size();
+        let Some(raw) = self.bytes.get(self.offset..self.offset + size) else {
+            return Err(ImportError::Parse("unexpected end of PLY body".into()));
+        };
+        self.offset += size;
+
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(raw);
+        if self.format == Format::BinaryBigEndian {
+            buffer[..size].reverse();
+        }
+
+        Ok(match ty {
+            ScalarType::I8 => buffer[0] as i8 as f64,
+            ScalarType::U8 => buffer[0] as f64,
+            ScalarType::I16 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            ScalarType::U16 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            ScalarType::I32 => i32::from_le_bytes(buffer[..4].try_into().unwrap()) as f64,
+            ScalarType::U32 => u32::from_le_bytes(buffer[..4].try_into().unwrap()) as f64,
+            ScalarType::F32 => f32::from_le_bytes(buffer[..4].try_into().unwrap()) as f64,
+            ScalarType::F64 => f64::from_le_bytes(buffer),
+        })
+    }
+}
+
+pub fn load(path: impl AsRef<Path>) -> ImportResult<TriangleMesh> {
+    parse(&fs::read(path)?)
+}
+
+/// Parses a PLY file into a triangle mesh, polygons are fan triangulated
+pub fn parse(bytes: &[u8]) -> ImportResult<TriangleMesh> {
+    const END_HEADER: &[u8] = b"end_header";
+    let header_end = bytes
+        .windows(END_HEADER.len())
+        .position(|window| window == END_HEADER)
+        .ok_or_else(|| ImportError::Parse("missing PLY `end_header`".into()))?;
+    let mut body_start = header_end + END_HEADER.len();
+    if bytes.get(body_start) == Some(&b'\r') {
+        body_start += 1;
+    }
+    if bytes.get(body_start) == Some(&b'\n') {
+        body_start += 1;
+    }
+
+    let header = String::from_utf8_lossy(&bytes[..header_end]);
+    let (format, elements) = parse_header(&header)?;
+    let mut body = Body {
+        format,
+        bytes: &bytes[body_start..],
+        offset: 0,
+    };
+
+    let vertex_count = elements
+        .iter()
+        .find(|element| element.name == "vertex")
+        .map_or(0, |element| element.count);
+    let mut mesh = TriangleMesh::default();
+    for element in elements.iter() {
+        for _ in 0..element.count {
+            match element.name.as_str() {
+                "vertex" => read_vertex(&mut body, element, &mut mesh)?,
+                "face" => read_face(&mut body, element, vertex_count, &mut mesh)?,
+                _ => {
+                    for property in element.properties.iter() {
+                        skip_property(&mut body, property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let vertex_count = mesh.positions.len();
+    if mesh.normals.len() != vertex_count {
+        mesh.normals.clear();
+    }
+    if mesh.uvs.len() != vertex_count {
+        mesh.uvs.clear();
+    }
+    if mesh.colors.len() != vertex_count {
+        mesh.colors.clear();
+    }
+    Ok(mesh)
+}
+
+fn parse_header(header: &str) -> ImportResult<(Format, Vec<Element>)> {
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(ImportError::Parse("missing `ply` magic number".into()));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", name, _] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(ImportError::Unsupported(format!("PLY format `{name}`"))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| ImportError::Parse(format!("invalid element count `{count}`")))?,
+                properties: vec![],
+            }),
+            ["property", "list", count, item, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| ImportError::Parse("property before element".into()))?;
+                element.properties.push(Property::List {
+                    name: name.to_string(),
+                    count: ScalarType::parse(count)?,
+                    item: ScalarType::parse(item)?,
+                });
+            }
+            ["property", ty, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| ImportError::Parse("property before element".into()))?;
+                element.properties.push(Property::Scalar {
+                    name: name.to_string(),
+                    ty: ScalarType::parse(ty)?,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let format = format.ok_or_else(|| ImportError::Parse("missing PLY format".into()))?;
+    Ok((format, elements))
+}
+
+fn read_vertex(body: &mut Body, element: &Element, mesh: &mut TriangleMesh) -> ImportResult<()> {
+    let mut position = Vector3::zero();
+    let mut normal = None::<Vector3>;
+    let mut uv = None::<(f64, f64)>;
+    let mut color = None::<Color>;
+
+    for property in element.properties.iter() {
+        match property {
+            Property::Scalar { name, ty } => {
+                let value = body.read(*ty)?;
+                match name.as_str() {
+                    "x" => position[0] = value,
+                    "y" => position[1] = value,
+                    "z" => position[2] = value,
+                    "nx" => normal.get_or_insert_with(Vector3::zero)[0] = value,
+                    "ny" => normal.get_or_insert_with(Vector3::zero)[1] = value,
+                    "nz" => normal.get_or_insert_with(Vector3::zero)[2] = value,
+                    "u" | "s" | "texture_u" | "texture_s" => uv.get_or_insert((0., 0.)).0 = value,
+                    "v" | "t" | "texture_v" | "texture_t" => uv.get_or_insert((0., 0.)).1 = value,
+                    "red" => color.get_or_insert_with(Color::white)[0] = ty.color(value),
+                    "green" => color.get_or_insert_with(Color::white)[1] = ty.color(value),
+                    "blue" => color.get_or_insert_with(Color::white)[2] = ty.color(value),
+                    _ => {}
+                }
+            }
+            _ => skip_property(body, property)?,
+        }
+    }
+
+    // ? Attributes are only kept when every vertex so far provided them
+    let index = mesh.positions.len();
+    mesh.positions.push(position);
+    if let Some(normal) = normal.filter(|_| mesh.normals.len() == index) {
+        mesh.normals.push(normal);
+    }
+    if let Some(uv) = uv.filter(|_| mesh.uvs.len() == index) {
+        mesh.uvs.push(uv);
+    }
+    if let Some(color) = color.filter(|_| mesh.colors.len() == index) {
+        mesh.colors.push(color);
+    }
+    Ok(())
+}
+
+fn read_face(
+    body: &mut Body,
+    element: &Element,
+    vertex_count: usize,
+    mesh: &mut TriangleMesh,
+) -> ImportResult<()> {
+    for property in element.properties.iter() {
+        match property {
+            Property::List { name, count, item }
+                if name == "vertex_indices" || name == "vertex_index" =>
+            {
+                let count = body.read_count(*count, *item)?;
+                let mut polygon = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let index = body.read(*item)?;
+                    if !(0. ..vertex_count as f64).contains(&index) {
+                        return Err(ImportError::Parse(format!(
+                            "invalid PLY vertex index {index}"
+                        )));
+                    }
+                    polygon.push(index as usize);
+                }
+                for i in 1..count.saturating_sub(1) {
+                    mesh.triangles
+                        .push([polygon[0], polygon[i], polygon[i + 1]]);
+                }
+            }
+            _ => skip_property(body, property)?,
+        }
+    }
+    Ok(())
+}
+
+fn skip_property(body: &mut Body, property: &Property) -> ImportResult<()> {
+    match property {
+        Property::Scalar { ty, .. } => {
+            body.read(*ty)?;
+        }
+        Property::List { count, item, .. } => {
+            let count = body.read_count(*count, *item)?;
+            for _ in 0..count {
+                body.read(*item)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII: &str = "ply
+format ascii 1.0
+comment unit quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+    fn binary(big_endian: bool) -> Vec<u8> {
+        let format = if big_endian {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        };
+        let mut bytes = format!(
+            "ply\nformat {format} 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+             property float z\nelement face 1\nproperty list uchar uint vertex_indices\nend_header\n"
+        )
+        .into_bytes();
+        let floats = [0f32, 0., 0., 1., 0., 0., 0., 1., 0.];
+        for value in floats {
+            bytes.extend(if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            });
+        }
+        bytes.push(3);
+        for index in 0u32..3 {
+            bytes.extend(if big_endian {
+                index.to_be_bytes()
+            } else {
+                index.to_le_bytes()
+            });
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_ascii_with_colors() {
+        let mesh = parse(ASCII.as_bytes()).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(mesh.colors[1].to_array(), [0., 1., 0.]);
+        assert!(mesh.normals.is_empty());
+    }
+
+    #[test]
+    fn parses_binary_in_both_byte_orders() {
+        for big_endian in [false, true] {
+            let mesh = parse(&binary(big_endian)).unwrap();
+            assert_eq!(mesh.positions[1].to_array(), [1., 0., 0.]);
+            assert_eq!(mesh.positions[2].to_array(), [0., 1., 0.]);
+            assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_binary() {
+        let bytes = binary(false);
+        assert!(matches!(
+            parse(&bytes[..bytes.len() - 2]),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_ascii() {
+        let truncated = &ASCII[..ASCII.len() - 4];
+        assert!(matches!(
+            parse(truncated.as_bytes()),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupt_list_length() {
+        let mut bytes = binary(false);
+        let count = bytes.len() - 13;
+        bytes[count] = 255;
+        assert!(matches!(parse(&bytes), Err(ImportError::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_missing_vertices() {
+        let text = ASCII.replace("4 0 1 2 3", "3 0 1 7");
+        assert!(matches!(parse(text.as_bytes()), Err(ImportError::Parse(_))));
+    }
+
+    #[test]
+    fn rejects_negative_indices() {
+        let text = ASCII.replace("4 0 1 2 3", "3 0 1 -1");
+        assert!(matches!(parse(text.as_bytes()), Err(ImportError::Parse(_))));
+    }
+
+    #[test]
+    fn linearizes_byte_colors() {
+        let text = ASCII.replacen("255 255 255", "128 128 128", 1);
+        let mesh = parse(text.as_bytes()).unwrap();
+        assert_eq!(mesh.colors[3].to_array(), [srgb_to_linear(128. / 255.); 3]);
+    }
+}
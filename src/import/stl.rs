@@ -0,0 +1,218 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::object::geometry::{mesh::TriangleMesh, vector::Vector3};
+
+use super::{ImportError, ImportResult};
+
+/// Default crease angle in degrees for [`load`]
+pub const DEFAULT_SMOOTHING_ANGLE: f64 = 30.;
+
+/// Loads a binary or ascii STL file, welding coincident vertices and
+/// smoothing normals between faces less than `smoothing_angle` degrees apart
+pub fn load(path: impl AsRef<Path>, smoothing_angle: f64) -> ImportResult<TriangleMesh> {
+    parse(&fs::read(path)?, smoothing_angle)
+}
+
+pub fn parse(bytes: &[u8], smoothing_angle: f64) -> ImportResult<TriangleMesh> {
+    let facets = if is_binary(bytes) {
+        parse_binary(bytes)?
+    } else {
+        parse_ascii(bytes)?
+    };
+    Ok(weld(&facets).with_smoothing_angle(smoothing_angle))
+}
+
+/// Binary files may also start with `solid`, so the size is checked first
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+    bytes.len() == 84 + 50 * count || !bytes.starts_with(b"solid")
+}
+
+fn parse_binary(bytes: &[u8]) -> ImportResult<Vec<[Vector3; 3]>> {
+    let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+    if bytes.len() < 84 + 50 * count {
+        return Err(ImportError::Parse(format!(
+            "STL declares {count} triangles but is only {} bytes",
+            bytes.len()
+        )));
+    }
+
+    let read_vector = |offset: usize| {
+        let component = |i: usize| {
+            let start = offset + 4 * i;
+            f32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+        };
+        Vector3::new(component(0), component(1), component(2))
+    };
+
+    // ? Each record is a normal, three vertices and a 2 byte attribute count
+    Ok((0..count)
+        .map(|i| {
+            let record = 84 + 50 * i;
+            [
+                read_vector(record + 12),
+                read_vector(record + 24),
+                read_vector(record + 36),
+            ]
+        })
+        .collect())
+}
+
+fn parse_ascii(bytes: &[u8]) -> ImportResult<Vec<[Vector3; 3]>> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut tokens = text.split_whitespace();
+    if tokens.next() != Some("solid") {
+        return Err(ImportError::Parse("missing STL `solid` keyword".into()));
+    }
+
+    let mut facets = vec![];
+    let mut facet = vec![];
+    while let Some(token) = tokens.next() {
+        match token {
+            "vertex" => {
+                let mut component = || -> ImportResult<f64> {
+                    let token = tokens
+                        .next()
+                        .ok_or_else(|| ImportError::Parse("unexpected end of STL".into()))?;
+                    token.parse().map_err(|_| {
+                        ImportError::Parse(format!("expected a number, found `{token}`"))
+                    })
+                };
+                facet.push(Vector3::new(component()?, component()?, component()?));
+            }
+            "endfacet" => {
+                if facet.len() != 3 {
+                    return Err(ImportError::Parse(format!(
+                        "STL facet has {} vertices",
+                        facet.len()
+                    )));
+                }
+                facets.push([facet[0], facet[1], facet[2]]);
+                facet.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(facets)
+}
+
+/// Merges vertices closer than a millionth of the model size
+fn weld(facets: &[[Vector3; 3]]) -> TriangleMesh {
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for vertex in facets.iter().flatten() {
+        min = min.min(vertex.x().min(vertex.y()).min(vertex.z()));
+        max = max.max(vertex.x().max(vertex.y()).max(vertex.z()));
+    }
+    let epsilon = ((max - min) * 1e-6).max(f64::MIN_POSITIVE);
+
+    let mut mesh = TriangleMesh::default();
+    let mut indices: HashMap<[i64; 3], usize> = HashMap::new();
+    for facet in facets.iter() {
+        let triangle = facet.map(|vertex| {
+            let key = vertex.to_array().map(|x| (x / epsilon).round() as i64);
+            *indices.entry(key).or_insert_with(|| {
+                mesh.positions.push(vertex);
+                mesh.positions.len() - 1
+            })
+        });
+
+        let degenerate =
+            triangle[0] == triangle[1] || triangle[1] == triangle[2] || triangle[2] == triangle[0];
+        if !degenerate {
+            mesh.triangles.push(triangle);
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII: &str = "solid square
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 0 0
+    vertex 1 1 0
+  endloop
+endfacet
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 1 0
+    vertex 0 1 0
+  endloop
+endfacet
+endsolid square
+";
+
+    fn binary(facets: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes.extend((facets.len() as u32).to_le_bytes());
+        for facet in facets {
+            bytes.extend([0u8; 12]);
+            for value in facet.iter().flatten() {
+                bytes.extend(value.to_le_bytes());
+            }
+            bytes.extend([0u8; 2]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_ascii_and_welds_shared_vertices() {
+        let mesh = parse(ASCII.as_bytes(), DEFAULT_SMOOTHING_ANGLE).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.triangles.len(), 2);
+    }
+
+    #[test]
+    fn parses_binary() {
+        let bytes = binary(&[
+            [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]],
+            [[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]],
+        ]);
+        let mesh = parse(&bytes, DEFAULT_SMOOTHING_ANGLE).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.triangles.len(), 2);
+        assert_eq!(mesh.positions[2].to_array(), [1., 1., 0.]);
+    }
+
+    #[test]
+    fn parses_binary_with_solid_header() {
+        let mut bytes = binary(&[[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]]);
+        bytes[..5].copy_from_slice(b"solid");
+        let mesh = parse(&bytes, DEFAULT_SMOOTHING_ANGLE).unwrap();
+        assert_eq!(mesh.triangles.len(), 1);
+    }
+
+    #[test]
+    fn drops_degenerate_facets() {
+        let bytes = binary(&[[[0., 0., 0.], [0., 0., 0.], [0., 1., 0.]]]);
+        let mesh = parse(&bytes, DEFAULT_SMOOTHING_ANGLE).unwrap();
+        assert!(mesh.triangles.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_binary() {
+        let bytes = binary(&[[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]; 2]);
+        assert!(matches!(
+            parse(&bytes[..bytes.len() - 10], DEFAULT_SMOOTHING_ANGLE),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_ascii() {
+        let truncated = &ASCII[..ASCII.find("vertex 0 1 0").unwrap() + 10];
+        assert!(matches!(
+            parse(truncated.as_bytes(), DEFAULT_SMOOTHING_ANGLE),
+            Err(ImportError::Parse(_))
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod import;
 pub mod object;
 pub mod render;
 pub mod util;
@@ -1,2 +1,4 @@
 pub mod geometry;
+pub mod light;
 pub mod material;
+pub mod texture;
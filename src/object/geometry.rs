@@ -1,2 +1,6 @@
+pub mod aabb;
+pub mod mesh;
+pub mod onb;
 pub mod sphere;
+pub mod transform;
 pub mod vector;
@@ -0,0 +1,89 @@
+use crate::view::ray::Ray;
+
+use super::vector::Vector3;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    min: Vector3,
+    max: Vector3,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3, max: Vector3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            min: Vector3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Vector3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn min(&self) -> &Vector3 {
+        &self.min
+    }
+
+    pub fn max(&self) -> &Vector3 {
+        &self.max
+    }
+
+    pub fn centroid(&self) -> Vector3 {
+        (self.min + self.max) / 2
+    }
+
+    pub fn extent(&self) -> Vector3 {
+        self.max - self.min
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.extent();
+        if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn grow(&mut self, point: &Vector3) {
+        for axis in 0..3 {
+            self.min[axis] = self.min[axis].min(point[axis]);
+            self.max[axis] = self.max[axis].max(point[axis]);
+        }
+    }
+
+    pub fn union(lhs: &Self, rhs: &Self) -> Self {
+        let mut aabb = *lhs;
+        aabb.grow(&rhs.min);
+        aabb.grow(&rhs.max);
+        aabb
+    }
+
+    /// Slab test, returns whether the ray enters the box within `range`
+    pub fn hit(&self, ray: &Ray, mut range: (f64, f64)) -> bool {
+        for axis in 0..3 {
+            let inverse_direction = 1. / ray.direction()[axis];
+            let mut t0 = (self.min[axis] - ray.origin()[axis]) * inverse_direction;
+            let mut t1 = (self.max[axis] - ray.origin()[axis]) * inverse_direction;
+            if inverse_direction < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            range.0 = if t0 > range.0 { t0 } else { range.0 };
+            range.1 = if t1 < range.1 { t1 } else { range.1 };
+            if range.1 < range.0 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
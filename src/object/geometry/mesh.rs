@@ -0,0 +1,283 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    object::material::{color::Color, Material},
+    view::ray::{Hit, Ray, RayHit},
+};
+
+use self::bvh::Bvh;
+
+use super::{aabb::Aabb, transform::Transform, vector::Vector3};
+
+pub mod bvh;
+pub mod displacement;
+pub mod subdivision;
+
+/// Indexed triangle soup shared by the mesh builders and loaders
+#[derive(Debug, Clone, Default)]
+pub struct TriangleMesh {
+    pub positions: Vec<Vector3>,
+    /// Per-vertex shading normals, either empty or one per position
+    pub normals: Vec<Vector3>,
+    /// Per-vertex texture coordinates, either empty or one per position
+    pub uvs: Vec<(f64, f64)>,
+    /// Per-vertex colors, either empty or one per position
+    pub colors: Vec<Color>,
+    /// Per-vertex tangents with the sign of the bitangent, which points
+    /// along `sign * normal × tangent`, either empty or one per position
+    pub tangents: Vec<(Vector3, f64)>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    pub fn new(positions: Vec<Vector3>, triangles: Vec<[usize; 3]>) -> Self {
+        Self {
+            positions,
+            normals: vec![],
+            uvs: vec![],
+            colors: vec![],
+            tangents: vec![],
+            triangles,
+        }
+    }
+
+    pub fn face_normal(&self, triangle: usize) -> Vector3 {
+        let [a, b, c] = self.triangles[triangle];
+        let (a, b, c) = (self.positions[a], self.positions[b], self.positions[c]);
+        Vector3::cross(&(b - a), &(c - a))
+    }
+
+    /// Replaces the vertex normals with area weighted averages of the face normals
+    pub fn with_smooth_normals(mut self) -> Self {
+        let mut normals = vec![Vector3::zero(); self.positions.len()];
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let face_normal = self.face_normal(index);
+            for &vertex in triangle {
+                normals[vertex] += face_normal;
+            }
+        }
+
+        self.normals = normals
+            .into_iter()
+            .map(|normal| {
+                if normal.is_near_zero() {
+                    normal
+                } else {
+                    normal.normalize()
+                }
+            })
+            .collect();
+        self
+    }
+
+    /// Smooth normals that only average faces within `max_angle` degrees of each
+    /// other, vertices on sharper edges are split so the edges stay faceted
+    pub fn with_smoothing_angle(self, max_angle: f64) -> Self {
+        let cos_threshold = max_angle.to_radians().cos();
+        let face_normals: Vec<Vector3> = (0..self.triangles.len())
+            .map(|triangle| self.face_normal(triangle))
+            .collect();
+        let mut vertex_faces = vec![vec![]; self.positions.len()];
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for &vertex in triangle {
+                vertex_faces[vertex].push(index);
+            }
+        }
+
+        let mut mesh = Self::default();
+        let mut corners: HashMap<(usize, [i64; 3]), usize> = HashMap::new();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            let face_direction = face_normals[index].normalize();
+            let mut output = [0; 3];
+            for (corner, &vertex) in triangle.iter().enumerate() {
+                let normal: Vector3 = vertex_faces[vertex]
+                    .iter()
+                    .map(|&face| face_normals[face])
+                    .filter(|normal| {
+                        Vector3::dot(&normal.normalize(), &face_direction) >= cos_threshold
+                    })
+                    .sum();
+                let normal = if normal.is_near_zero() {
+                    face_direction
+                } else {
+                    normal.normalize()
+                };
+
+                let key = (vertex, normal.to_array().map(|x| (x * 1e6).round() as i64));
+                output[corner] = *corners.entry(key).or_insert_with(|| {
+                    mesh.positions.push(self.positions[vertex]);
+                    mesh.normals.push(normal);
+                    if !self.uvs.is_empty() {
+                        mesh.uvs.push(self.uvs[vertex]);
+                    }
+                    if !self.colors.is_empty() {
+                        mesh.colors.push(self.colors[vertex]);
+                    }
+                    if !self.tangents.is_empty() {
+                        mesh.tangents.push(self.tangents[vertex]);
+                    }
+                    mesh.positions.len() - 1
+                });
+            }
+            mesh.triangles.push(output);
+        }
+        mesh
+    }
+
+    pub fn transformed(mut self, transform: &Transform) -> Self {
+        for position in self.positions.iter_mut() {
+            *position = transform.transform_point(position);
+        }
+        let normal_transform = transform.normal_transform();
+        for normal in self.normals.iter_mut() {
+            *normal = normal_transform.transform_vector(normal).normalize();
+        }
+        // ? Mirroring transforms flip the winding and thus the face normals,
+        // and the handedness of the tangent frames
+        let mirrored = transform.determinant() < 0.;
+        for (tangent, sign) in self.tangents.iter_mut() {
+            *tangent = transform.transform_vector(tangent).normalize();
+            if mirrored {
+                *sign = -*sign;
+            }
+        }
+        if mirrored {
+            for triangle in self.triangles.iter_mut() {
+                triangle.swap(1, 2);
+            }
+        }
+        self
+    }
+
+    pub fn triangle_bounds(&self, triangle: usize) -> Aabb {
+        let mut aabb = Aabb::empty();
+        for &vertex in &self.triangles[triangle] {
+            aabb.grow(&self.positions[vertex]);
+        }
+        aabb
+    }
+
+    /// Möller–Trumbore intersection, returns `(t, b1, b2)`
+    pub fn intersect_triangle(
+        &self,
+        triangle: usize,
+        ray: &Ray,
+        range: (f64, f64),
+    ) -> Option<(f64, f64, f64)> {
+        let [a, b, c] = self.triangles[triangle];
+        let a = self.positions[a];
+        let edge1 = self.positions[b] - a;
+        let edge2 = self.positions[c] - a;
+
+        let p = Vector3::cross(ray.direction(), &edge2);
+        let determinant = Vector3::dot(&edge1, &p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse_determinant = 1. / determinant;
+
+        let s = *ray.origin() - a;
+        let b1 = Vector3::dot(&s, &p) * inverse_determinant;
+        if !(0. ..=1.).contains(&b1) {
+            return None;
+        }
+
+        let q = Vector3::cross(&s, &edge1);
+        let b2 = Vector3::dot(ray.direction(), &q) * inverse_determinant;
+        if b2 < 0. || b1 + b2 > 1. {
+            return None;
+        }
+
+        let t = Vector3::dot(&edge2, &q) * inverse_determinant;
+        if t < range.0 || t >= range.1 {
+            return None;
+        }
+        Some((t, b1, b2))
+    }
+}
+
+/// Renderable triangle mesh with its own bounding volume hierarchy
+pub struct Mesh {
+    data: TriangleMesh,
+    bvh: Bvh,
+    material: Arc<dyn Material>,
+}
+
+impl Mesh {
+    pub fn new(data: TriangleMesh, material: Arc<dyn Material>) -> Self {
+        let bvh = Bvh::new(&data);
+        Self {
+            data,
+            bvh,
+            material,
+        }
+    }
+
+    pub fn data(&self) -> &TriangleMesh {
+        &self.data
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bvh.bounds()
+    }
+}
+
+impl Hit for Mesh {
+    fn hit(&self, ray: &Ray, range: (f64, f64)) -> Option<RayHit> {
+        let (triangle, t, b1, b2) = self.bvh.hit(&self.data, ray, range)?;
+        let [a, b, c] = self.data.triangles[triangle];
+        let b0 = 1. - b1 - b2;
+
+        let geometric_normal = self.data.face_normal(triangle).normalize();
+        let mut hit = RayHit::new(ray, t, geometric_normal, self.material.clone());
+        if !self.data.normals.is_empty() {
+            let shading_normal =
+                b0 * self.data.normals[a] + b1 * self.data.normals[b] + b2 * self.data.normals[c];
+            if !shading_normal.is_near_zero() {
+                let shading_normal = shading_normal.normalize();
+                hit.normal = if hit.front_face {
+                    shading_normal
+                } else {
+                    -shading_normal
+                };
+            }
+        }
+
+        let (uv_a, uv_b, uv_c) = if self.data.uvs.is_empty() {
+            ((0., 0.), (1., 0.), (1., 1.))
+        } else {
+            (self.data.uvs[a], self.data.uvs[b], self.data.uvs[c])
+        };
+        hit.uv = (
+            b0 * uv_a.0 + b1 * uv_b.0 + b2 * uv_c.0,
+            b0 * uv_a.1 + b1 * uv_b.1 + b2 * uv_c.1,
+        );
+
+        if !self.data.colors.is_empty() {
+            hit.color = Some(
+                b0 * self.data.colors[a] + b1 * self.data.colors[b] + b2 * self.data.colors[c],
+            );
+        }
+
+        // ? Solve the point partial derivatives from the uv deltas
+        let duv02 = (uv_a.0 - uv_c.0, uv_a.1 - uv_c.1);
+        let duv12 = (uv_b.0 - uv_c.0, uv_b.1 - uv_c.1);
+        let dp02 = self.data.positions[a] - self.data.positions[c];
+        let dp12 = self.data.positions[b] - self.data.positions[c];
+        let determinant = duv02.0 * duv12.1 - duv02.1 * duv12.0;
+        if determinant.abs() > 1e-12 {
+            hit.tangent = (duv12.1 * dp02 - duv02.1 * dp12) / determinant;
+            hit.bitangent = (duv02.0 * dp12 - duv12.0 * dp02) / determinant;
+        }
+        if !self.data.tangents.is_empty() {
+            let (tangent_a, sign) = self.data.tangents[a];
+            let tangent =
+                b0 * tangent_a + b1 * self.data.tangents[b].0 + b2 * self.data.tangents[c].0;
+            if !tangent.is_near_zero() {
+                hit.tangent = tangent.normalize();
+                hit.bitangent = sign * Vector3::cross(&hit.outward_normal(), &hit.tangent);
+            }
+        }
+        Some(hit)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{object::geometry::aabb::Aabb, view::ray::Ray};
+
+use super::TriangleMesh;
+
+const LEAF_SIZE: usize = 4;
+
+enum Node {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        end: usize,
+    },
+    Branch {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over the triangles of a [`TriangleMesh`]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    triangles: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(mesh: &TriangleMesh) -> Self {
+        let bounds: Vec<Aabb> = (0..mesh.triangles.len())
+            .map(|triangle| mesh.triangle_bounds(triangle))
+            .collect();
+        let mut bvh = Self {
+            nodes: vec![],
+            triangles: (0..mesh.triangles.len()).collect(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(&bounds, 0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.nodes
+            .first()
+            .map(|node| *node.bounds())
+            .unwrap_or_default()
+    }
+
+    fn build(&mut self, triangle_bounds: &[Aabb], start: usize, end: usize) -> usize {
+        let mut bounds = Aabb::empty();
+        let mut centroid_bounds = Aabb::empty();
+        for &triangle in &self.triangles[start..end] {
+            bounds = Aabb::union(&bounds, &triangle_bounds[triangle]);
+            centroid_bounds.grow(&triangle_bounds[triangle].centroid());
+        }
+
+        let index = self.nodes.len();
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf { bounds, start, end });
+            return index;
+        }
+
+        let axis = centroid_bounds.longest_axis();
+        let middle = (start + end) / 2;
+        self.triangles[start..end].select_nth_unstable_by(middle - start, |&lhs, &rhs| {
+            let lhs = triangle_bounds[lhs].centroid()[axis];
+            let rhs = triangle_bounds[rhs].centroid()[axis];
+            lhs.total_cmp(&rhs)
+        });
+
+        // ? Children are patched in after they are built
+        self.nodes.push(Node::Branch {
+            bounds,
+            left: 0,
+            right: 0,
+        });
+        let left_child = self.build(triangle_bounds, start, middle);
+        let right_child = self.build(triangle_bounds, middle, end);
+        if let Node::Branch { left, right, .. } = &mut self.nodes[index] {
+            *left = left_child;
+            *right = right_child;
+        }
+        index
+    }
+
+    /// Returns the closest `(triangle, t, b1, b2)` within `range`
+    pub fn hit(
+        &self,
+        mesh: &TriangleMesh,
+        ray: &Ray,
+        mut range: (f64, f64),
+    ) -> Option<(usize, f64, f64, f64)> {
+        let mut closest = None;
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds().hit(ray, range) {
+                continue;
+            }
+
+            match node {
+                Node::Leaf { start, end, .. } => {
+                    for &triangle in &self.triangles[*start..*end] {
+                        if let Some((t, b1, b2)) = mesh.intersect_triangle(triangle, ray, range) {
+                            range.1 = t;
+                            closest = Some((triangle, t, b1, b2));
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+
+        closest
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    object::{geometry::vector::Vector3, texture::Texture},
+    view::camera::Camera,
+};
+
+use super::TriangleMesh;
+
+const MAX_PASSES: u32 = 12;
+/// Triangle count above which tessellation stops splitting edges
+const DEFAULT_MAX_TRIANGLES: usize = 2_000_000;
+/// Share of the image by which edges may lie outside of it and still be
+/// split, since displacement can move them into view
+const FRUSTUM_MARGIN: f64 = 0.1;
+
+/// Moves mesh vertices along their normals by a scalar texture
+///
+/// The mesh is first tessellated until no edge is longer than
+/// `max_edge_length` pixels when seen through the render camera. Edges
+/// outside of the view are left alone and splitting stops once the mesh
+/// reaches the triangle budget.
+pub struct Displacement {
+    texture: Arc<dyn Texture>,
+    scale: f64,
+    max_edge_length: f64,
+    max_triangles: usize,
+}
+
+impl Displacement {
+    pub fn new(texture: Arc<dyn Texture>, scale: f64, max_edge_length: f64) -> Self {
+        Self {
+            texture,
+            scale,
+            max_edge_length,
+            max_triangles: DEFAULT_MAX_TRIANGLES,
+        }
+    }
+
+    pub fn with_max_triangles(mut self, max_triangles: usize) -> Self {
+        self.max_triangles = max_triangles;
+        self
+    }
+
+    pub fn apply(
+        &self,
+        mesh: &TriangleMesh,
+        camera: &Camera,
+        resolution: (u32, u32),
+    ) -> TriangleMesh {
+        let mut mesh = if mesh.normals.is_empty() {
+            mesh.clone().with_smooth_normals()
+        } else {
+            mesh.clone()
+        };
+        // ? Displaced vertices get new normals, which the old tangents would
+        // no longer lie across
+        mesh.tangents.clear();
+
+        for _ in 0..MAX_PASSES {
+            if !self.tessellate(&mut mesh, camera, resolution) {
+                break;
+            }
+        }
+
+        for vertex in 0..mesh.positions.len() {
+            let point = mesh.positions[vertex];
+            let (u, v) = mesh.uvs.get(vertex).copied().unwrap_or_default();
+            let height = self.texture.scalar(u, v, &point);
+            mesh.positions[vertex] = point + self.scale * height * mesh.normals[vertex];
+        }
+
+        mesh.with_smooth_normals()
+    }
+
+    fn screen_length(
+        &self,
+        a: &Vector3,
+        b: &Vector3,
+        camera: &Camera,
+        resolution: (u32, u32),
+    ) -> f64 {
+        match (camera.project(a), camera.project(b)) {
+            // ? Edges entirely to one side of the image cannot cross it
+            (Some((ua, va)), Some((ub, vb)))
+                if ua.max(ub) < -FRUSTUM_MARGIN
+                    || ua.min(ub) > 1. + FRUSTUM_MARGIN
+                    || va.max(vb) < -FRUSTUM_MARGIN
+                    || va.min(vb) > 1. + FRUSTUM_MARGIN =>
+            {
+                0.
+            }
+            (Some((ua, va)), Some((ub, vb))) => {
+                let dx = (ub - ua) * resolution.0 as f64;
+                let dy = (vb - va) * resolution.1 as f64;
+                dx.hypot(dy)
+            }
+            _ => 0.,
+        }
+    }
+
+    /// Splits every edge that is too long on screen while the triangle
+    /// budget allows, returns whether any was split
+    fn tessellate(&self, mesh: &mut TriangleMesh, camera: &Camera, resolution: (u32, u32)) -> bool {
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        // ? Splitting an edge adds a triangle on each of its two sides
+        let mut triangles = mesh.triangles.len();
+        'triangles: for triangle in mesh.triangles.iter() {
+            for i in 0..3 {
+                let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                let key = (a.min(b), a.max(b));
+                if midpoints.contains_key(&key) {
+                    continue;
+                }
+
+                let length =
+                    self.screen_length(&mesh.positions[a], &mesh.positions[b], camera, resolution);
+                if length > self.max_edge_length {
+                    if triangles + 2 > self.max_triangles {
+                        break 'triangles;
+                    }
+                    triangles += 2;
+                    midpoints.insert(key, mesh.positions.len());
+                    mesh.positions
+                        .push((mesh.positions[a] + mesh.positions[b]) / 2);
+                    let normal = mesh.normals[a] + mesh.normals[b];
+                    mesh.normals.push(if normal.is_near_zero() {
+                        mesh.normals[a]
+                    } else {
+                        normal.normalize()
+                    });
+                    if !mesh.uvs.is_empty() {
+                        let (uvs_a, uvs_b) = (mesh.uvs[a], mesh.uvs[b]);
+                        mesh.uvs
+                            .push(((uvs_a.0 + uvs_b.0) / 2., (uvs_a.1 + uvs_b.1) / 2.));
+                    }
+                    if !mesh.colors.is_empty() {
+                        mesh.colors.push((mesh.colors[a] + mesh.colors[b]) / 2);
+                    }
+                }
+            }
+        }
+
+        if midpoints.is_empty() {
+            return false;
+        }
+
+        let midpoint = |a: usize, b: usize| midpoints.get(&(a.min(b), a.max(b))).copied();
+        mesh.triangles = mesh
+            .triangles
+            .iter()
+            .flat_map(|triangle| {
+                let split = [
+                    midpoint(triangle[0], triangle[1]),
+                    midpoint(triangle[1], triangle[2]),
+                    midpoint(triangle[2], triangle[0]),
+                ];
+                split_triangle(*triangle, split)
+            })
+            .collect();
+        true
+    }
+}
+
+/// Splits a triangle by the midpoints of its edges `[v0v1, v1v2, v2v0]`
+fn split_triangle(triangle: [usize; 3], split: [Option<usize>; 3]) -> Vec<[usize; 3]> {
+    let count = split.iter().filter(|midpoint| midpoint.is_some()).count();
+    // ? Rotate so that the split edges come first
+    let rotation = (0..3)
+        .find(|&k| match count {
+            1 => split[k].is_some(),
+            2 => split[(k + 2) % 3].is_none(),
+            _ => true,
+        })
+        .unwrap_or(0);
+    let v = |i: usize| triangle[(i + rotation) % 3];
+    let m = |i: usize| split[(i + rotation) % 3].unwrap_or_default();
+
+    match count {
+        0 => vec![triangle],
+        1 => vec![[v(0), m(0), v(2)], [m(0), v(1), v(2)]],
+        2 => vec![[m(0), v(1), m(1)], [v(0), m(0), m(1)], [v(0), m(1), v(2)]],
+        _ => vec![
+            [v(0), m(0), m(2)],
+            [m(0), v(1), m(1)],
+            [m(2), m(1), v(2)],
+            [m(0), m(1), m(2)],
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{
+            geometry::mesh::Mesh,
+            material::{color::Color, lambertian::Lambertian},
+            texture::SolidColor,
+        },
+        vec3,
+        view::ray::{Hit, Ray},
+    };
+
+    fn camera() -> Camera {
+        Camera::new(
+            vec3!(0, 0, 3),
+            Vector3::zero(),
+            Vector3::up(),
+            60.,
+            1.,
+            0.,
+            3.,
+        )
+    }
+
+    #[test]
+    fn moves_vertices_along_their_normals() {
+        let triangle = TriangleMesh::new(
+            vec![vec3!(-1, -1, 0), vec3!(1, -1, 0), vec3!(0, 1, 0)],
+            vec![[0, 1, 2]],
+        );
+        let height = Arc::new(SolidColor::new(Color::white() * 0.25));
+        let displaced = Displacement::new(height, 2., 1e9).apply(&triangle, &camera(), (64, 64));
+
+        assert_eq!(displaced.triangles.len(), 1);
+        assert!(displaced
+            .positions
+            .iter()
+            .all(|p| (p.z() - 0.5).abs() < 1e-12));
+        assert!(displaced
+            .normals
+            .iter()
+            .all(|n| (*n - vec3!(0, 0, 1)).is_near_zero()));
+    }
+
+    #[test]
+    fn tessellates_vertex_colors() {
+        let mut triangle = TriangleMesh::new(
+            vec![vec3!(-1, -1, 0), vec3!(1, -1, 0), vec3!(0, 1, 0)],
+            vec![[0, 1, 2]],
+        );
+        triangle.colors = vec![
+            Color::new(1, 0, 0),
+            Color::new(0, 1, 0),
+            Color::new(0, 0, 1),
+        ];
+        let flat = Arc::new(SolidColor::new(Color::black()));
+        let displaced = Displacement::new(flat, 1., 4.).apply(&triangle, &camera(), (64, 64));
+
+        assert!(displaced.triangles.len() > 1);
+        assert_eq!(displaced.colors.len(), displaced.positions.len());
+        let mesh = Mesh::new(displaced, Arc::new(Lambertian::new(Color::white())));
+        let hit = mesh
+            .hit(
+                &Ray::of(vec3!(0, -0.5, 3), vec3!(0, 0, -1)),
+                (0.001, f64::INFINITY),
+            )
+            .expect("the displaced triangle should be hit");
+        let color = hit.color.expect("the hit should carry a vertex color");
+        assert!((color.x() + color.y() + color.z() - 1.).abs() < 1e-9);
+    }
+}
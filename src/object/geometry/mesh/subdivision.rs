@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use crate::object::geometry::vector::Vector3;
+
+use super::TriangleMesh;
+
+type Edge = (usize, usize);
+
+fn edge(a: usize, b: usize) -> Edge {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Scheme {
+    /// Loop subdivision, polygons are fan triangulated first
+    Loop,
+    /// Catmull–Clark subdivision, produces quads after the first level
+    CatmullClark,
+}
+
+/// Coarse polygonal cage to be refined by [`ControlMesh::subdivide`]
+///
+/// Boundary edges are treated as infinitely sharp creases.
+#[derive(Debug, Clone, Default)]
+pub struct ControlMesh {
+    pub positions: Vec<Vector3>,
+    pub faces: Vec<Vec<usize>>,
+    creases: HashMap<Edge, f64>,
+}
+
+struct EdgeInfo {
+    faces: Vec<usize>,
+    /// Vertex opposite to the edge in each adjacent triangle (Loop only)
+    opposite: Vec<usize>,
+}
+
+struct Topology {
+    edges: HashMap<Edge, EdgeInfo>,
+    vertex_edges: Vec<Vec<Edge>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl ControlMesh {
+    pub fn new(positions: Vec<Vector3>, faces: Vec<Vec<usize>>) -> Self {
+        Self {
+            positions,
+            faces,
+            creases: HashMap::new(),
+        }
+    }
+
+    /// Marks the edge `a`–`b` as a crease, sharpness decreases by one per level
+    /// so fractional values produce semi-sharp creases
+    pub fn with_crease(mut self, a: usize, b: usize, sharpness: f64) -> Self {
+        self.creases.insert(edge(a, b), sharpness);
+        self
+    }
+
+    pub fn crease(&self, a: usize, b: usize) -> f64 {
+        self.creases.get(&edge(a, b)).copied().unwrap_or(0.)
+    }
+
+    /// Refines the cage `levels` times and triangulates it with smooth normals
+    pub fn subdivide(&self, scheme: Scheme, levels: u32) -> TriangleMesh {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = match scheme {
+                Scheme::Loop => mesh.loop_step(),
+                Scheme::CatmullClark => mesh.catmull_clark_step(),
+            };
+        }
+        mesh.triangulate().with_smooth_normals()
+    }
+
+    pub fn triangulate(&self) -> TriangleMesh {
+        let triangles = self
+            .faces
+            .iter()
+            .flat_map(|face| {
+                (1..face.len().saturating_sub(1)).map(|i| [face[0], face[i], face[i + 1]])
+            })
+            .collect();
+        TriangleMesh::new(self.positions.clone(), triangles)
+    }
+
+    fn topology(&self) -> Topology {
+        let mut edges: HashMap<Edge, EdgeInfo> = HashMap::new();
+        let mut vertex_edges = vec![vec![]; self.positions.len()];
+        let mut vertex_faces = vec![vec![]; self.positions.len()];
+
+        for (index, face) in self.faces.iter().enumerate() {
+            for (i, &vertex) in face.iter().enumerate() {
+                vertex_faces[vertex].push(index);
+                let next = face[(i + 1) % face.len()];
+                let key = edge(vertex, next);
+                let info = edges.entry(key).or_insert_with(|| {
+                    vertex_edges[key.0].push(key);
+                    vertex_edges[key.1].push(key);
+                    EdgeInfo {
+                        faces: vec![],
+                        opposite: vec![],
+                    }
+                });
+                info.faces.push(index);
+                if face.len() == 3 {
+                    info.opposite.push(face[(i + 2) % 3]);
+                }
+            }
+        }
+
+        Topology {
+            edges,
+            vertex_edges,
+            vertex_faces,
+        }
+    }
+
+    fn sharpness(&self, topology: &Topology, key: &Edge) -> f64 {
+        if topology.edges[key].faces.len() != 2 {
+            f64::INFINITY
+        } else {
+            self.crease(key.0, key.1)
+        }
+    }
+
+    /// Blends a smooth edge point with the crease midpoint by sharpness
+    fn edge_point(&self, key: &Edge, sharpness: f64, smooth: impl FnOnce() -> Vector3) -> Vector3 {
+        let midpoint = (self.positions[key.0] + self.positions[key.1]) / 2;
+        if sharpness >= 1. {
+            midpoint
+        } else if sharpness > 0. {
+            Vector3::lerp(&smooth(), &midpoint, sharpness)
+        } else {
+            smooth()
+        }
+    }
+
+    /// Applies the crease and corner rules on top of the smooth vertex rule
+    fn vertex_point(&self, topology: &Topology, vertex: usize, smooth: Vector3) -> Vector3 {
+        let position = self.positions[vertex];
+        let sharp_edges: Vec<(Edge, f64)> = topology.vertex_edges[vertex]
+            .iter()
+            .map(|key| (*key, self.sharpness(topology, key)))
+            .filter(|(_, sharpness)| *sharpness > 0.)
+            .collect();
+
+        let sharp = match sharp_edges.len() {
+            0 | 1 => return smooth,
+            2 => {
+                let neighbors: Vector3 = sharp_edges
+                    .iter()
+                    .map(|((a, b), _)| self.positions[if *a == vertex { *b } else { *a }])
+                    .sum();
+                0.75 * position + 0.125 * neighbors
+            }
+            _ => position,
+        };
+
+        let sharpness = sharp_edges
+            .iter()
+            .map(|(_, sharpness)| sharpness)
+            .sum::<f64>()
+            / sharp_edges.len() as f64;
+        if sharpness >= 1. {
+            sharp
+        } else {
+            Vector3::lerp(&smooth, &sharp, sharpness)
+        }
+    }
+
+    fn child_creases(
+        &self,
+        topology: &Topology,
+        edge_points: &HashMap<Edge, usize>,
+    ) -> HashMap<Edge, f64> {
+        let mut creases = HashMap::new();
+        for key in topology.edges.keys() {
+            let sharpness = self.crease(key.0, key.1);
+            if sharpness > 1. {
+                let midpoint = edge_points[key];
+                creases.insert(edge(key.0, midpoint), sharpness - 1.);
+                creases.insert(edge(midpoint, key.1), sharpness - 1.);
+            }
+        }
+        creases
+    }
+
+    fn loop_step(&self) -> Self {
+        let mesh = if self.faces.iter().all(|face| face.len() == 3) {
+            self.clone()
+        } else {
+            Self {
+                faces: self
+                    .triangulate()
+                    .triangles
+                    .iter()
+                    .map(|t| t.to_vec())
+                    .collect(),
+                ..self.clone()
+            }
+        };
+        let topology = mesh.topology();
+
+        let mut positions: Vec<Vector3> = (0..mesh.positions.len())
+            .map(|vertex| {
+                let neighbors: Vec<Vector3> = topology.vertex_edges[vertex]
+                    .iter()
+                    .map(|(a, b)| mesh.positions[if *a == vertex { *b } else { *a }])
+                    .collect();
+                // ? Unreferenced vertices have no neighbors to average
+                if neighbors.is_empty() {
+                    return mesh.positions[vertex];
+                }
+                let n = neighbors.len() as f64;
+                let cosine_term = 0.375 + 0.25 * (2. * std::f64::consts::PI / n).cos();
+                let beta = (0.625 - cosine_term * cosine_term) / n;
+                let smooth = (1. - n * beta) * mesh.positions[vertex]
+                    + beta * neighbors.into_iter().sum::<Vector3>();
+                mesh.vertex_point(&topology, vertex, smooth)
+            })
+            .collect();
+
+        let mut edge_points = HashMap::new();
+        for (key, info) in topology.edges.iter() {
+            let sharpness = mesh.sharpness(&topology, key);
+            let point = mesh.edge_point(key, sharpness, || {
+                let opposite: Vector3 = info.opposite.iter().map(|&v| mesh.positions[v]).sum();
+                0.375 * (mesh.positions[key.0] + mesh.positions[key.1]) + 0.125 * opposite
+            });
+            edge_points.insert(*key, positions.len());
+            positions.push(point);
+        }
+
+        let faces = mesh
+            .faces
+            .iter()
+            .flat_map(|face| {
+                let (a, b, c) = (face[0], face[1], face[2]);
+                let ab = edge_points[&edge(a, b)];
+                let bc = edge_points[&edge(b, c)];
+                let ca = edge_points[&edge(c, a)];
+                [
+                    vec![a, ab, ca],
+                    vec![b, bc, ab],
+                    vec![c, ca, bc],
+                    vec![ab, bc, ca],
+                ]
+            })
+            .collect();
+
+        Self {
+            positions,
+            faces,
+            creases: mesh.child_creases(&topology, &edge_points),
+        }
+    }
+
+    fn catmull_clark_step(&self) -> Self {
+        let topology = self.topology();
+
+        let face_points: Vec<Vector3> = self
+            .faces
+            .iter()
+            .map(|face| {
+                face.iter().map(|&v| self.positions[v]).sum::<Vector3>() / face.len() as f64
+            })
+            .collect();
+
+        let mut positions: Vec<Vector3> = (0..self.positions.len())
+            .map(|vertex| {
+                let faces = &topology.vertex_faces[vertex];
+                let edges = &topology.vertex_edges[vertex];
+                if edges.is_empty() {
+                    return self.positions[vertex];
+                }
+                let n = edges.len() as f64;
+                let face_average = faces.iter().map(|&f| face_points[f]).sum::<Vector3>()
+                    / faces.len().max(1) as f64;
+                let edge_average = edges
+                    .iter()
+                    .map(|(a, b)| (self.positions[*a] + self.positions[*b]) / 2)
+                    .sum::<Vector3>()
+                    / n;
+                let smooth =
+                    (face_average + 2. * edge_average + (n - 3.) * self.positions[vertex]) / n;
+                self.vertex_point(&topology, vertex, smooth)
+            })
+            .collect();
+
+        let mut edge_points = HashMap::new();
+        for (key, info) in topology.edges.iter() {
+            let sharpness = self.sharpness(&topology, key);
+            let point = self.edge_point(key, sharpness, || {
+                let faces: Vector3 = info.faces.iter().map(|&f| face_points[f]).sum();
+                (self.positions[key.0] + self.positions[key.1] + faces) / 4
+            });
+            edge_points.insert(*key, positions.len());
+            positions.push(point);
+        }
+
+        let face_offset = positions.len();
+        positions.extend(face_points);
+
+        let faces = self
+            .faces
+            .iter()
+            .enumerate()
+            .flat_map(|(index, face)| {
+                let k = face.len();
+                let edge_points = &edge_points;
+                (0..k).map(move |i| {
+                    let vertex = face[i];
+                    let next = face[(i + 1) % k];
+                    let previous = face[(i + k - 1) % k];
+                    vec![
+                        vertex,
+                        edge_points[&edge(vertex, next)],
+                        face_offset + index,
+                        edge_points[&edge(previous, vertex)],
+                    ]
+                })
+            })
+            .collect();
+
+        Self {
+            positions,
+            faces,
+            creases: self.child_creases(&topology, &edge_points),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3;
+
+    fn tetrahedron() -> ControlMesh {
+        ControlMesh::new(
+            vec![
+                vec3!(1, 1, 1),
+                vec3!(1, -1, -1),
+                vec3!(-1, 1, -1),
+                vec3!(-1, -1, 1),
+            ],
+            vec![vec![0, 1, 2], vec![0, 3, 1], vec![0, 2, 3], vec![1, 3, 2]],
+        )
+    }
+
+    #[test]
+    fn loop_splits_every_triangle_in_four() {
+        let once = tetrahedron().subdivide(Scheme::Loop, 1);
+        assert_eq!((once.positions.len(), once.triangles.len()), (10, 16));
+        let twice = tetrahedron().subdivide(Scheme::Loop, 2);
+        assert_eq!((twice.positions.len(), twice.triangles.len()), (34, 64));
+        // ? Smoothing only ever averages, so the surface shrinks into the cage
+        assert!(twice
+            .positions
+            .iter()
+            .all(|p| p.x().abs() + p.y().abs() + p.z().abs() < 3.));
+    }
+
+    #[test]
+    fn catmull_clark_makes_quads() {
+        let cube = ControlMesh::new(
+            vec![
+                vec3!(-1, -1, -1),
+                vec3!(1, -1, -1),
+                vec3!(1, 1, -1),
+                vec3!(-1, 1, -1),
+                vec3!(-1, -1, 1),
+                vec3!(1, -1, 1),
+                vec3!(1, 1, 1),
+                vec3!(-1, 1, 1),
+            ],
+            vec![
+                vec![0, 3, 2, 1],
+                vec![4, 5, 6, 7],
+                vec![0, 1, 5, 4],
+                vec![2, 3, 7, 6],
+                vec![1, 2, 6, 5],
+                vec![0, 4, 7, 3],
+            ],
+        );
+        let mesh = cube.subdivide(Scheme::CatmullClark, 1);
+        assert_eq!((mesh.positions.len(), mesh.triangles.len()), (26, 48));
+    }
+
+    #[test]
+    fn sharp_creases_keep_their_edge_straight() {
+        let mesh = tetrahedron()
+            .with_crease(0, 1, 10.)
+            .subdivide(Scheme::Loop, 1);
+        let midpoint = vec3!(1, 0, 0);
+        assert!(mesh
+            .positions
+            .iter()
+            .any(|p| (*p - midpoint).is_near_zero()));
+    }
+}
@@ -0,0 +1,62 @@
+use super::vector::Vector3;
+
+/// Orthonormal basis around a normal, `w` is the normal
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    u: Vector3,
+    v: Vector3,
+    w: Vector3,
+}
+
+impl Onb {
+    pub fn from_normal(normal: &Vector3) -> Self {
+        let w = normal.normalize();
+        let helper = if w.x().abs() > 0.9 {
+            Vector3::up()
+        } else {
+            Vector3::new(1, 0, 0)
+        };
+        let v = Vector3::cross(&w, &helper).normalize();
+        let u = Vector3::cross(&v, &w);
+        Self { u, v, w }
+    }
+
+    /// Basis whose `u` axis follows `tangent` projected onto the normal plane
+    pub fn from_normal_tangent(normal: &Vector3, tangent: &Vector3) -> Self {
+        let w = normal.normalize();
+        let projected = *tangent - Vector3::dot(tangent, &w) * w;
+        if projected.is_near_zero() {
+            return Self::from_normal(normal);
+        }
+
+        let u = projected.normalize();
+        let v = Vector3::cross(&w, &u);
+        Self { u, v, w }
+    }
+
+    pub fn u(&self) -> &Vector3 {
+        &self.u
+    }
+
+    pub fn v(&self) -> &Vector3 {
+        &self.v
+    }
+
+    pub fn w(&self) -> &Vector3 {
+        &self.w
+    }
+
+    /// Local to world coordinates
+    pub fn local(&self, vector: &Vector3) -> Vector3 {
+        vector.x() * self.u + vector.y() * self.v + vector.z() * self.w
+    }
+
+    /// World to local coordinates
+    pub fn to_local(&self, vector: &Vector3) -> Vector3 {
+        Vector3::new(
+            Vector3::dot(vector, &self.u),
+            Vector3::dot(vector, &self.v),
+            Vector3::dot(vector, &self.w),
+        )
+    }
+}
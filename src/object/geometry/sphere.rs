@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     object::material::Material,
@@ -50,16 +50,23 @@ impl Hit for Sphere {
             }
         }
 
-        let hitpoint = ray.at(root);
-        let normal = (hitpoint - self.center) / self.radius;
-        let mut hit = RayHit {
-            point: hitpoint,
-            normal,
-            front_face: false,
-            t: root,
-            material: self.material.clone(),
-        };
-        hit.set_face_normal(ray, normal);
+        let normal = (ray.at(root) - self.center) / self.radius;
+        let mut hit = RayHit::new(ray, root, normal, self.material.clone());
+
+        let theta = (-normal.y()).clamp(-1., 1.).acos();
+        let phi = f64::atan2(-normal.z(), normal.x()) + PI;
+        hit.uv = (phi / (2. * PI), theta / PI);
+        hit.tangent = 2.
+            * PI
+            * self.radius
+            * Vector3::new(theta.sin() * phi.sin(), 0, theta.sin() * phi.cos());
+        hit.bitangent = PI
+            * self.radius
+            * Vector3::new(
+                -theta.cos() * phi.cos(),
+                theta.sin(),
+                theta.cos() * phi.sin(),
+            );
         Some(hit)
     }
 }
@@ -0,0 +1,210 @@
+use std::ops::Mul;
+
+use super::vector::Vector3;
+
+type Matrix = [[f64; 4]; 4];
+
+/// Affine transform stored as a row-major 4x4 matrix
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+}
+
+impl Transform {
+    pub fn new(matrix: Matrix) -> Self {
+        Self { matrix }
+    }
+
+    pub fn identity() -> Self {
+        let mut matrix = [[0.; 4]; 4];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = 1.;
+        }
+        Self { matrix }
+    }
+
+    /// From column-major storage, as used by glTF and pbrt
+    pub fn from_columns(columns: [[f64; 4]; 4]) -> Self {
+        let mut matrix = [[0.; 4]; 4];
+        for (i, column) in columns.iter().enumerate() {
+            for (j, value) in column.iter().enumerate() {
+                matrix[j][i] = *value;
+            }
+        }
+        Self { matrix }
+    }
+
+    pub fn translate(offset: Vector3) -> Self {
+        let mut transform = Self::identity();
+        for axis in 0..3 {
+            transform.matrix[axis][3] = offset[axis];
+        }
+        transform
+    }
+
+    pub fn scale(factors: Vector3) -> Self {
+        let mut transform = Self::identity();
+        for axis in 0..3 {
+            transform.matrix[axis][axis] = factors[axis];
+        }
+        transform
+    }
+
+    /// Rotation by `degrees` around `axis`, counter-clockwise looking down the axis
+    pub fn rotate(degrees: f64, axis: Vector3) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let mut transform = Self::identity();
+        transform.matrix[0][0] = x * x + (1. - x * x) * cos;
+        transform.matrix[0][1] = x * y * (1. - cos) - z * sin;
+        transform.matrix[0][2] = x * z * (1. - cos) + y * sin;
+        transform.matrix[1][0] = x * y * (1. - cos) + z * sin;
+        transform.matrix[1][1] = y * y + (1. - y * y) * cos;
+        transform.matrix[1][2] = y * z * (1. - cos) - x * sin;
+        transform.matrix[2][0] = x * z * (1. - cos) - y * sin;
+        transform.matrix[2][1] = y * z * (1. - cos) + x * sin;
+        transform.matrix[2][2] = z * z + (1. - z * z) * cos;
+        transform
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    pub fn transform_point(&self, point: &Vector3) -> Vector3 {
+        let m = &self.matrix;
+        let mut result = self.transform_vector(point);
+        for axis in 0..3 {
+            result[axis] += m[axis][3];
+        }
+        let w = m[3][0] * point.x() + m[3][1] * point.y() + m[3][2] * point.z() + m[3][3];
+        if w != 1. && w != 0. {
+            result /= w;
+        }
+        result
+    }
+
+    pub fn transform_vector(&self, vector: &Vector3) -> Vector3 {
+        let m = &self.matrix;
+        let row = |i: usize| m[i][0] * vector.x() + m[i][1] * vector.y() + m[i][2] * vector.z();
+        Vector3::new(row(0), row(1), row(2))
+    }
+
+    /// Inverse transpose, which keeps transformed normals perpendicular
+    pub fn normal_transform(&self) -> Self {
+        let inverse = self.inverse().unwrap_or_else(Self::identity);
+        let mut matrix = [[0.; 4]; 4];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = inverse.matrix[j][i];
+            }
+        }
+        Self { matrix }
+    }
+
+    pub fn transform_normal(&self, normal: &Vector3) -> Vector3 {
+        self.normal_transform().transform_vector(normal)
+    }
+
+    /// Determinant of the linear part, negative when the transform mirrors
+    pub fn determinant(&self) -> f64 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Gauss–Jordan inversion with partial pivoting
+    pub fn inverse(&self) -> Option<Self> {
+        let mut m = self.matrix;
+        let mut inverse = Self::identity().matrix;
+
+        for column in 0..4 {
+            let pivot =
+                (column..4).max_by(|&a, &b| m[a][column].abs().total_cmp(&m[b][column].abs()))?;
+            if m[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            m.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1. / m[column][column];
+            for j in 0..4 {
+                m[column][j] *= scale;
+                inverse[column][j] *= scale;
+            }
+
+            for row in 0..4 {
+                if row == column {
+                    continue;
+                }
+                let factor = m[row][column];
+                for j in 0..4 {
+                    m[row][j] -= factor * m[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+
+        Some(Self { matrix: inverse })
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut matrix = [[0.; 4]; 4];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.matrix[i][k] * rhs.matrix[k][j]).sum();
+            }
+        }
+        Self { matrix }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(lhs: &Transform, rhs: &Transform) {
+        for (a, b) in lhs.matrix.iter().flatten().zip(rhs.matrix.iter().flatten()) {
+            assert!((a - b).abs() < 1e-9, "{lhs:?} != {rhs:?}");
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = Transform::translate(Vector3::new(1, -2, 3))
+            * Transform::rotate(30., Vector3::new(1, 1, 0))
+            * Transform::scale(Vector3::new(2, 0.5, -1));
+        let inverse = transform.inverse().unwrap();
+        assert_near(&(transform * inverse), &Transform::identity());
+        assert_near(&(inverse * transform), &Transform::identity());
+    }
+
+    #[test]
+    fn inverse_needs_pivoting() {
+        // ? Swapping axes leaves zeros on the diagonal
+        let swap = Transform::from_columns([
+            [0., 1., 0., 0.],
+            [1., 0., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ]);
+        assert_near(&swap.inverse().unwrap(), &swap);
+    }
+
+    #[test]
+    fn singular_transform_has_no_inverse() {
+        assert!(Transform::scale(Vector3::new(1, 0, 1)).inverse().is_none());
+    }
+}
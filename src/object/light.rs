@@ -0,0 +1,822 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    object::{
+        geometry::{
+            mesh::{Mesh, TriangleMesh},
+            onb::Onb,
+            sphere::Sphere,
+            vector::Vector3,
+        },
+        material::{
+            bsdf::sample_cosine_hemisphere, color::Color, diffuse_light::DiffuseLight, Material,
+        },
+    },
+    view::ray::{Hit, HitTarget, Ray},
+};
+
+/// Incident light at a point, sampled from a [`Light`]
+#[derive(Debug, Copy, Clone)]
+pub struct LightSample {
+    /// Unit direction from the point towards the light
+    pub direction: Vector3,
+    /// Distance to the light, infinite for lights at infinity
+    pub distance: f64,
+    /// Surface normal at the sampled point, zero for lights without a surface
+    pub normal: Vector3,
+    /// Incident radiance, or irradiance for delta lights
+    pub radiance: Color,
+    /// Solid angle density of `direction`, one for delta lights
+    pub pdf: f64,
+    /// Whether the light can only be reached by sampling it
+    pub delta: bool,
+}
+
+impl LightSample {
+    /// Traces a shadow ray from `point` towards the light
+    pub fn is_visible(&self, world: &HitTarget, point: &Vector3) -> bool {
+        let shadow_ray = Ray::of(*point, self.direction);
+        world
+            .hit(&shadow_ray, (0.001, self.distance * (1. - 1e-6)))
+            .is_none()
+    }
+}
+
+/// Ray leaving a light, sampled to start light subpaths
+#[derive(Debug, Copy, Clone)]
+pub struct LightEmission {
+    pub origin: Vector3,
+    /// Unit direction the light leaves along
+    pub direction: Vector3,
+    /// Surface normal at the origin, the direction itself for lights
+    /// without a surface
+    pub normal: Vector3,
+    pub radiance: Color,
+    /// Area density of the origin, one for point lights
+    pub pdf_position: f64,
+    /// Solid angle density of the direction
+    pub pdf_direction: f64,
+    /// Whether the light is a single point
+    pub delta: bool,
+}
+
+pub trait Light: Send + Sync {
+    /// Samples the light as seen from `point` with the random numbers `u`
+    fn sample_li(&self, point: &Vector3, u: (f64, f64)) -> Option<LightSample>;
+
+    /// Density with which [`Light::sample_li`] picks the unit `direction`,
+    /// given that a ray from `point` along it hits an emitter at `distance`.
+    /// Zero unless that emitter is this light, and always zero for delta
+    /// lights and lights that rays cannot hit, whose samples are then not
+    /// weighted against material sampling.
+    fn pdf_li(&self, _point: &Vector3, _direction: &Vector3, _distance: f64) -> f64 {
+        0.
+    }
+
+    /// Samples a ray leaving the light, `None` for lights that cannot
+    /// start paths
+    fn sample_le(&self, _u: (f64, f64), _v: (f64, f64)) -> Option<LightEmission> {
+        None
+    }
+
+    /// Densities `(position, direction)` with which [`Light::sample_le`]
+    /// starts a ray at `point` on the light along the unit `direction`.
+    /// For infinite lights `point` may be anywhere along the ray.
+    fn pdf_le(&self, _point: &Vector3, _direction: &Vector3) -> (f64, f64) {
+        (0., 0.)
+    }
+
+    /// Whether the light surrounds the scene and is what rays leaving it see
+    fn is_infinite(&self) -> bool {
+        false
+    }
+}
+
+/// Uniformly distributed unit vector from two uniform numbers
+fn sample_uniform_sphere(u: (f64, f64)) -> Vector3 {
+    let z = 1. - 2. * u.0;
+    let radius = (1. - z * z).max(0.).sqrt();
+    let phi = 2. * PI * u.1;
+    Vector3::new(radius * phi.cos(), radius * phi.sin(), z)
+}
+
+/// Uniformly distributed point on the unit disk from two uniform numbers
+fn sample_uniform_disk(u: (f64, f64)) -> Vector3 {
+    let radius = u.0.sqrt();
+    let phi = 2. * PI * u.1;
+    Vector3::new(radius * phi.cos(), radius * phi.sin(), 0)
+}
+
+/// Isotropic point light
+pub struct PointLight {
+    position: Vector3,
+    /// Radiant intensity
+    intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Vector3, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample_li(&self, point: &Vector3, _: (f64, f64)) -> Option<LightSample> {
+        let offset = self.position - *point;
+        let distance = offset.magnitude();
+        if distance == 0. {
+            return None;
+        }
+        Some(LightSample {
+            direction: offset / distance,
+            distance,
+            normal: Vector3::zero(),
+            radiance: self.intensity / (distance * distance),
+            pdf: 1.,
+            delta: true,
+        })
+    }
+
+    fn sample_le(&self, u: (f64, f64), _: (f64, f64)) -> Option<LightEmission> {
+        let direction = sample_uniform_sphere(u);
+        Some(LightEmission {
+            origin: self.position,
+            direction,
+            normal: direction,
+            radiance: self.intensity,
+            pdf_position: 1.,
+            pdf_direction: 1. / (4. * PI),
+            delta: true,
+        })
+    }
+
+    fn pdf_le(&self, _: &Vector3, _: &Vector3) -> (f64, f64) {
+        (0., 1. / (4. * PI))
+    }
+}
+
+/// Point light restricted to a cone, fading out between the inner and outer angles
+pub struct SpotLight {
+    position: Vector3,
+    /// Unit direction the spot points at
+    direction: Vector3,
+    intensity: Color,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    /// Cone angles are measured in degrees from the axis
+    pub fn new(
+        position: Vector3,
+        at: Vector3,
+        intensity: Color,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> Self {
+        let outer_angle = outer_angle.max(inner_angle);
+        Self {
+            position,
+            direction: (at - position).normalize(),
+            intensity,
+            cos_inner: inner_angle.to_radians().cos(),
+            cos_outer: outer_angle.to_radians().cos(),
+        }
+    }
+
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_inner {
+            return 1.;
+        }
+        if cos_theta <= self.cos_outer {
+            return 0.;
+        }
+        let t = (cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        t * t * (3. - 2. * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_li(&self, point: &Vector3, _: (f64, f64)) -> Option<LightSample> {
+        let offset = self.position - *point;
+        let distance = offset.magnitude();
+        if distance == 0. {
+            return None;
+        }
+        let direction = offset / distance;
+        let falloff = self.falloff(Vector3::dot(&-direction, &self.direction));
+        if falloff == 0. {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            normal: Vector3::zero(),
+            radiance: falloff * self.intensity / (distance * distance),
+            pdf: 1.,
+            delta: true,
+        })
+    }
+
+    fn sample_le(&self, u: (f64, f64), _: (f64, f64)) -> Option<LightEmission> {
+        // ? Uniform sampling of the outer cone
+        let cos_theta = 1. - u.0 * (1. - self.cos_outer);
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * u.1;
+        let local = Vector3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        let direction = Onb::from_normal(&self.direction).local(&local);
+        Some(LightEmission {
+            origin: self.position,
+            direction,
+            normal: direction,
+            radiance: self.falloff(cos_theta) * self.intensity,
+            pdf_position: 1.,
+            pdf_direction: 1. / (2. * PI * (1. - self.cos_outer)),
+            delta: true,
+        })
+    }
+
+    fn pdf_le(&self, _: &Vector3, direction: &Vector3) -> (f64, f64) {
+        if Vector3::dot(direction, &self.direction) < self.cos_outer {
+            return (0., 0.);
+        }
+        (0., 1. / (2. * PI * (1. - self.cos_outer)))
+    }
+}
+
+/// Light at infinity such as the sun, a non zero angular diameter gives
+/// soft shadows
+pub struct DirectionalLight {
+    /// Unit direction towards the light
+    direction: Vector3,
+    /// Irradiance on a surface facing the light
+    irradiance: Color,
+    cos_max: f64,
+}
+
+impl DirectionalLight {
+    /// `direction` points towards the light, `angular_diameter` is in degrees
+    pub fn new(direction: Vector3, irradiance: Color, angular_diameter: f64) -> Self {
+        Self {
+            direction: direction.normalize(),
+            irradiance,
+            cos_max: (angular_diameter / 2.).to_radians().cos(),
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample_li(&self, _: &Vector3, u: (f64, f64)) -> Option<LightSample> {
+        if self.cos_max >= 1. {
+            return Some(LightSample {
+                direction: self.direction,
+                distance: f64::INFINITY,
+                normal: Vector3::zero(),
+                radiance: self.irradiance,
+                pdf: 1.,
+                delta: true,
+            });
+        }
+
+        // ? Uniform sampling of the cone subtended by the disk
+        let solid_angle = 2. * PI * (1. - self.cos_max);
+        let cos_theta = 1. - u.0 * (1. - self.cos_max);
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * u.1;
+        let local = Vector3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        Some(LightSample {
+            direction: Onb::from_normal(&self.direction).local(&local),
+            distance: f64::INFINITY,
+            normal: Vector3::zero(),
+            radiance: self.irradiance / solid_angle,
+            pdf: 1. / solid_angle,
+            delta: false,
+        })
+    }
+}
+
+/// Spherical area light, its geometry from [`SphereLight::sphere`] must be
+/// part of the world, and rendered with an integrator that weights hits on
+/// it against light samples so it is not counted twice
+pub struct SphereLight {
+    center: Vector3,
+    radius: f64,
+    /// Emitted radiance
+    emit: Color,
+}
+
+impl SphereLight {
+    pub fn new(center: Vector3, radius: f64, emit: Color) -> Self {
+        Self {
+            center,
+            radius,
+            emit,
+        }
+    }
+
+    pub fn sphere(&self) -> Sphere {
+        Sphere::new(
+            self.center,
+            self.radius,
+            Arc::new(DiffuseLight::new(self.emit)),
+        )
+    }
+
+    /// Cosine of the half angle the sphere subtends from `point`, `None` inside
+    fn cos_theta_max(&self, point: &Vector3) -> Option<f64> {
+        let distance2 = (self.center - *point).magnitude_squared();
+        let radius2 = self.radius * self.radius;
+        if distance2 <= radius2 {
+            return None;
+        }
+        Some((1. - radius2 / distance2).max(0.).sqrt())
+    }
+}
+
+impl Light for SphereLight {
+    fn sample_li(&self, point: &Vector3, u: (f64, f64)) -> Option<LightSample> {
+        let cos_theta_max = self.cos_theta_max(point)?;
+        let to_center = self.center - *point;
+        let center_distance = to_center.magnitude();
+
+        // ? Uniform sampling of the cone of directions that reach the sphere
+        let cos_theta = 1. - u.0 * (1. - cos_theta_max);
+        let sin2_theta = (1. - cos_theta * cos_theta).max(0.);
+        let phi = 2. * PI * u.1;
+        let local = Vector3::new(
+            sin2_theta.sqrt() * phi.cos(),
+            sin2_theta.sqrt() * phi.sin(),
+            cos_theta,
+        );
+        let direction = Onb::from_normal(&to_center).local(&local).normalize();
+        let distance = center_distance * cos_theta
+            - (self.radius * self.radius - center_distance * center_distance * sin2_theta)
+                .max(0.)
+                .sqrt();
+
+        let solid_angle = 2. * PI * (1. - cos_theta_max);
+        Some(LightSample {
+            direction,
+            distance,
+            normal: (*point + distance * direction - self.center) / self.radius,
+            radiance: self.emit,
+            pdf: 1. / solid_angle,
+            delta: false,
+        })
+    }
+
+    fn pdf_li(&self, point: &Vector3, direction: &Vector3, distance: f64) -> f64 {
+        let Some(cos_theta_max) = self.cos_theta_max(point) else {
+            return 0.;
+        };
+        if distance.is_infinite() {
+            return 0.;
+        }
+        let hit_point = *point + distance * *direction;
+        let on_surface = ((hit_point - self.center).magnitude() - self.radius).abs();
+        if on_surface > 1e-6 * self.radius.max(distance) {
+            return 0.;
+        }
+        1. / (2. * PI * (1. - cos_theta_max))
+    }
+
+    fn sample_le(&self, u: (f64, f64), v: (f64, f64)) -> Option<LightEmission> {
+        let normal = sample_uniform_sphere(u);
+        let local = sample_cosine_hemisphere(v);
+        Some(LightEmission {
+            origin: self.center + self.radius * normal,
+            direction: Onb::from_normal(&normal).local(&local),
+            normal,
+            radiance: self.emit,
+            pdf_position: 1. / (4. * PI * self.radius * self.radius),
+            pdf_direction: local.z() / PI,
+            delta: false,
+        })
+    }
+
+    fn pdf_le(&self, point: &Vector3, direction: &Vector3) -> (f64, f64) {
+        let normal = (*point - self.center).normalize();
+        (
+            1. / (4. * PI * self.radius * self.radius),
+            Vector3::dot(&normal, direction).max(0.) / PI,
+        )
+    }
+}
+
+/// Emissive triangle mesh, sampled uniformly over its area
+pub struct MeshLight {
+    mesh: Arc<Mesh>,
+    /// Running totals of the triangle areas
+    areas: Vec<f64>,
+    /// Emitted radiance
+    emit: Color,
+    two_sided: bool,
+}
+
+impl MeshLight {
+    pub fn new(data: TriangleMesh, emit: Color, two_sided: bool) -> Self {
+        let material: Arc<dyn Material> = if two_sided {
+            Arc::new(DiffuseLight::two_sided(emit))
+        } else {
+            Arc::new(DiffuseLight::new(emit))
+        };
+        let mut total = 0.;
+        let areas = (0..data.triangles.len())
+            .map(|triangle| {
+                total += data.face_normal(triangle).magnitude() / 2.;
+                total
+            })
+            .collect();
+        Self {
+            mesh: Arc::new(Mesh::new(data, material)),
+            areas,
+            emit,
+            two_sided,
+        }
+    }
+
+    /// The emitting surface, to be added to the world
+    pub fn mesh(&self) -> Arc<Mesh> {
+        self.mesh.clone()
+    }
+
+    fn area(&self) -> f64 {
+        self.areas.last().copied().unwrap_or(0.)
+    }
+
+    /// Uniformly distributed point on the mesh with its unit face normal
+    fn sample_point(&self, u: (f64, f64)) -> Option<(Vector3, Vector3)> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let target = u.0 * area;
+        let triangle = self
+            .areas
+            .partition_point(|&total| total <= target)
+            .min(self.areas.len() - 1);
+        // ? The number that picked the triangle is stretched back over [0, 1)
+        let start = if triangle > 0 {
+            self.areas[triangle - 1]
+        } else {
+            0.
+        };
+        let span = self.areas[triangle] - start;
+        let u0 = if span > 0. {
+            ((target - start) / span).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let data = self.mesh.data();
+        let [a, b, c] = data.triangles[triangle];
+        let root = u0.sqrt();
+        let point = (1. - root) * data.positions[a]
+            + root * (1. - u.1) * data.positions[b]
+            + root * u.1 * data.positions[c];
+        Some((point, data.face_normal(triangle).normalize()))
+    }
+
+    /// Unit face normal of the triangle `point` lies on
+    fn normal_at(&self, point: &Vector3) -> Option<Vector3> {
+        let data = self.mesh.data();
+        (0..data.triangles.len()).find_map(|triangle| {
+            let [a, b, c] = data.triangles[triangle].map(|v| data.positions[v]);
+            let normal = data.face_normal(triangle);
+            let area2 = normal.magnitude_squared();
+            if area2 == 0. {
+                return None;
+            }
+            let offset = *point - a;
+            let plane_distance = Vector3::dot(&offset, &normal) / area2.sqrt();
+            let scale = (b - a).magnitude().max((c - a).magnitude());
+            if plane_distance.abs() > 1e-6 * scale {
+                return None;
+            }
+            let b1 = Vector3::dot(&Vector3::cross(&offset, &(c - a)), &normal) / area2;
+            let b2 = Vector3::dot(&Vector3::cross(&(b - a), &offset), &normal) / area2;
+            let inside = |b: f64| (-1e-6..=1. + 1e-6).contains(&b);
+            (inside(b1) && inside(b2) && inside(1. - b1 - b2)).then(|| normal / area2.sqrt())
+        })
+    }
+
+    /// Cosine of the unit `direction` leaving the surface, zero on the
+    /// side that does not emit
+    fn emitting_cosine(&self, normal: &Vector3, direction: &Vector3) -> f64 {
+        let cos_theta = Vector3::dot(normal, direction);
+        if self.two_sided {
+            cos_theta.abs()
+        } else {
+            cos_theta.max(0.)
+        }
+    }
+}
+
+impl Light for MeshLight {
+    fn sample_li(&self, point: &Vector3, u: (f64, f64)) -> Option<LightSample> {
+        let (position, normal) = self.sample_point(u)?;
+        let offset = position - *point;
+        let distance = offset.magnitude();
+        if distance == 0. {
+            return None;
+        }
+        let direction = offset / distance;
+        let cos_light = self.emitting_cosine(&normal, &-direction);
+        if cos_light <= 0. {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            normal,
+            radiance: self.emit,
+            pdf: distance * distance / (cos_light * self.area()),
+            delta: false,
+        })
+    }
+
+    fn pdf_li(&self, point: &Vector3, direction: &Vector3, distance: f64) -> f64 {
+        if distance.is_infinite() {
+            return 0.;
+        }
+        let Some(hit) = self
+            .mesh
+            .hit(&Ray::of(*point, *direction), (0.001, f64::INFINITY))
+        else {
+            return 0.;
+        };
+        if (hit.distance - distance).abs() > 1e-6 * distance.max(1.)
+            || !(hit.front_face || self.two_sided)
+        {
+            return 0.;
+        }
+        let cos_light = Vector3::dot(&hit.geometric_normal, direction).abs();
+        if cos_light == 0. {
+            return 0.;
+        }
+        distance * distance / (cos_light * self.area())
+    }
+
+    fn sample_le(&self, u: (f64, f64), v: (f64, f64)) -> Option<LightEmission> {
+        let (origin, mut normal) = self.sample_point(u)?;
+        // ? Two sided meshes pick a side with the first number and reuse it
+        let mut v = v;
+        if self.two_sided {
+            if v.0 < 0.5 {
+                normal = -normal;
+                v.0 *= 2.;
+            } else {
+                v.0 = 2. * v.0 - 1.;
+            }
+        }
+        let local = sample_cosine_hemisphere(v);
+        let sides = if self.two_sided { 2. } else { 1. };
+        Some(LightEmission {
+            origin,
+            direction: Onb::from_normal(&normal).local(&local),
+            normal,
+            radiance: self.emit,
+            pdf_position: 1. / self.area(),
+            pdf_direction: local.z() / (PI * sides),
+            delta: false,
+        })
+    }
+
+    fn pdf_le(&self, point: &Vector3, direction: &Vector3) -> (f64, f64) {
+        let Some(normal) = self.normal_at(point) else {
+            return (0., 0.);
+        };
+        let sides = if self.two_sided { 2. } else { 1. };
+        (
+            1. / self.area(),
+            self.emitting_cosine(&normal, direction) / (PI * sides),
+        )
+    }
+}
+
+/// Sky gradient seen by rays leaving the scene along `direction`
+pub fn sky(direction: &Vector3) -> Color {
+    let t = 0.5 * (direction.normalize().y() + 1.);
+    Vector3::lerp(&Color::ones(), &Color::new(0.5, 0.7, 1), t)
+}
+
+/// Radiance of the rays leaving the scene
+#[derive(Debug, Copy, Clone)]
+pub enum Background {
+    /// The gradient of [`sky`]
+    Sky,
+    /// The same radiance from every direction, black to leave only the lights
+    Uniform(Color),
+}
+
+impl Background {
+    /// Radiance arriving from `direction`
+    pub fn radiance(&self, direction: &Vector3) -> Color {
+        match self {
+            Background::Sky => sky(direction),
+            Background::Uniform(color) => *color,
+        }
+    }
+}
+
+/// The gradient of [`sky`] as a light, so it can be sampled
+/// directly and start light paths. Those paths enter through a disk as large
+/// as the sphere of `center` and `radius`, which has to enclose the part of
+/// the scene seen by the camera.
+pub struct SkyLight {
+    center: Vector3,
+    radius: f64,
+}
+
+impl SkyLight {
+    pub fn new(center: Vector3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    /// Radiance arriving from the unit `direction`
+    fn radiance(&self, direction: &Vector3) -> Color {
+        sky(direction)
+    }
+}
+
+impl Light for SkyLight {
+    fn sample_li(&self, _: &Vector3, u: (f64, f64)) -> Option<LightSample> {
+        let direction = sample_uniform_sphere(u);
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            normal: Vector3::zero(),
+            radiance: self.radiance(&direction),
+            pdf: 1. / (4. * PI),
+            delta: false,
+        })
+    }
+
+    fn pdf_li(&self, _: &Vector3, _: &Vector3, distance: f64) -> f64 {
+        if distance.is_finite() {
+            return 0.;
+        }
+        1. / (4. * PI)
+    }
+
+    fn sample_le(&self, u: (f64, f64), v: (f64, f64)) -> Option<LightEmission> {
+        let to_light = sample_uniform_sphere(u);
+        let basis = Onb::from_normal(&to_light);
+        let disk = sample_uniform_disk(v);
+        Some(LightEmission {
+            origin: self.center
+                + self.radius * (to_light + disk.x() * *basis.u() + disk.y() * *basis.v()),
+            direction: -to_light,
+            normal: -to_light,
+            radiance: self.radiance(&to_light),
+            pdf_position: 1. / (PI * self.radius * self.radius),
+            pdf_direction: 1. / (4. * PI),
+            delta: false,
+        })
+    }
+
+    fn pdf_le(&self, point: &Vector3, direction: &Vector3) -> (f64, f64) {
+        // ? Rays only start on the disk, points beside or behind it are out of reach
+        let offset = *point - self.center;
+        let along = Vector3::dot(&offset, direction);
+        let across = offset - along * *direction;
+        if along < -self.radius || across.magnitude_squared() > self.radius * self.radius {
+            return (0., 1. / (4. * PI));
+        }
+        (1. / (PI * self.radius * self.radius), 1. / (4. * PI))
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3;
+
+    #[test]
+    fn point_light_falls_off_with_the_square_of_distance() {
+        let light = PointLight::new(vec3!(0, 4, 0), Color::white() * 8.);
+        let near = light.sample_li(&vec3!(0, 2, 0), (0.5, 0.5)).unwrap();
+        let far = light.sample_li(&vec3!(0, 0, 0), (0.5, 0.5)).unwrap();
+        assert!(near.delta && far.delta);
+        assert_eq!(near.direction.to_array(), [0., 1., 0.]);
+        assert_eq!(near.radiance.to_array(), [2., 2., 2.]);
+        assert_eq!(far.radiance.to_array(), [0.5, 0.5, 0.5]);
+        assert_eq!(far.distance, 4.);
+    }
+
+    #[test]
+    fn spot_light_fades_between_its_cones() {
+        let light = SpotLight::new(vec3!(0, 1, 0), Vector3::zero(), Color::white(), 20., 40.);
+        // ? Intensity towards points on the ground at `degrees` off the axis
+        let intensity = |degrees: f64| {
+            let angle = degrees.to_radians();
+            let point = vec3!(angle.tan(), 0, 0);
+            light.sample_li(&point, (0.5, 0.5)).map_or(0., |sample| {
+                sample.radiance.x() * sample.distance * sample.distance
+            })
+        };
+        assert!((intensity(0.) - 1.).abs() < 1e-12);
+        assert!((intensity(19.) - 1.).abs() < 1e-12);
+        let (closer, wider) = (intensity(25.), intensity(35.));
+        assert!(
+            0. < wider && wider < closer && closer < 1.,
+            "{closer} {wider}"
+        );
+        assert_eq!(intensity(45.), 0.);
+    }
+
+    #[test]
+    fn directional_light_delivers_its_irradiance() {
+        let sun = DirectionalLight::new(vec3!(0, 1, 1), Color::white() * 3., 0.);
+        let sample = sun.sample_li(&Vector3::zero(), (0.3, 0.8)).unwrap();
+        assert!(sample.delta && sample.distance.is_infinite());
+        assert_eq!(sample.radiance.to_array(), [3., 3., 3.]);
+
+        // ? A disk sun spreads the same irradiance over its cone
+        let sun = DirectionalLight::new(vec3!(0, 1, 0), Color::white() * 3., 10.);
+        for u in [(0.1, 0.2), (0.9, 0.6)] {
+            let sample = sun.sample_li(&Vector3::zero(), u).unwrap();
+            assert!(!sample.delta);
+            assert!(sample.direction.y() >= 5_f64.to_radians().cos() - 1e-12);
+            assert!((sample.radiance.x() / sample.pdf - 3.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn shadow_rays_stop_at_occluders() {
+        let mut world = HitTarget::new();
+        world.push(Arc::new(Sphere::new(
+            vec3!(0, 2, 0),
+            0.5,
+            Arc::new(DiffuseLight::new(Color::white())),
+        )));
+        let light = PointLight::new(vec3!(0, 4, 0), Color::white());
+        let sample = light.sample_li(&Vector3::zero(), (0.5, 0.5)).unwrap();
+        assert!(!sample.is_visible(&world, &Vector3::zero()));
+        let beside = vec3!(2, 0, 0);
+        let sample = light.sample_li(&beside, (0.5, 0.5)).unwrap();
+        assert!(sample.is_visible(&world, &beside));
+    }
+
+    #[test]
+    fn sphere_light_densities_match_its_samples() {
+        let light = SphereLight::new(vec3!(0, 3, 0), 1., Color::white());
+        let point = vec3!(0.5, 0, 0.2);
+        for u in [(0.1, 0.7), (0.6, 0.2), (0.95, 0.5)] {
+            let sample = light.sample_li(&point, u).unwrap();
+            let on_sphere = point + sample.distance * sample.direction;
+            assert!(((on_sphere - vec3!(0, 3, 0)).magnitude() - 1.).abs() < 1e-9);
+            let pdf = light.pdf_li(&point, &sample.direction, sample.distance);
+            assert!(
+                (sample.pdf - pdf).abs() < 1e-9 * pdf,
+                "{} {pdf}",
+                sample.pdf
+            );
+        }
+        // ? Hits on other objects along the same direction are not the light
+        let sample = light.sample_li(&point, (0.5, 0.5)).unwrap();
+        assert_eq!(
+            light.pdf_li(&point, &sample.direction, sample.distance / 2.),
+            0.
+        );
+        assert!(light.sample_li(&vec3!(0, 3.5, 0), (0.5, 0.5)).is_none());
+    }
+
+    #[test]
+    fn mesh_light_densities_match_its_samples() {
+        let quad = TriangleMesh::new(
+            vec![
+                vec3!(-1, 2, -1),
+                vec3!(1, 2, -1),
+                vec3!(1, 2, 1),
+                vec3!(-1, 2, 1),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        );
+        let light = MeshLight::new(quad, Color::white(), false);
+        let point = vec3!(0.3, 0, 0.2);
+        for u in [(0.1, 0.7), (0.6, 0.2), (0.95, 0.5)] {
+            let sample = light.sample_li(&point, u).unwrap();
+            let pdf = light.pdf_li(&point, &sample.direction, sample.distance);
+            assert!(
+                (sample.pdf - pdf).abs() < 1e-9 * pdf,
+                "{} {pdf}",
+                sample.pdf
+            );
+
+            let emission = light.sample_le(u, u).unwrap();
+            let (pdf_position, pdf_direction) = light.pdf_le(&emission.origin, &emission.direction);
+            assert!((pdf_position - emission.pdf_position).abs() < 1e-12);
+            assert!((pdf_direction - emission.pdf_direction).abs() < 1e-9);
+        }
+        // ? The quad faces down, so it does not light points above it
+        assert!(light.sample_li(&vec3!(0, 3, 0), (0.5, 0.5)).is_none());
+    }
+}
@@ -1,17 +1,138 @@
-use crate::view::ray::{Ray, RayHit};
+use crate::{
+    object::geometry::vector::Vector3,
+    view::ray::{Ray, RayHit},
+};
 
-use self::color::Color;
+use self::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+};
 
+pub mod bsdf;
+pub mod coated;
 pub mod color;
+pub mod conductor;
+pub mod cutout;
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod fresnel;
 pub mod lambertian;
+pub mod measured;
 pub mod metal;
+pub mod microfacet;
+pub mod mix;
+pub mod normal_map;
+pub mod oren_nayar;
+pub mod principled;
+pub mod rough_dielectric;
+pub mod subsurface;
+pub mod thin_film;
+pub mod toon;
 
 pub trait Material: Send + Sync {
     fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter>;
+
+    fn emitted(&self, _hit: &RayHit) -> Color {
+        Color::black()
+    }
+
+    /// BRDF value for the unit world space directions `wo` towards the
+    /// viewer and `wi` towards the light, black for purely specular materials
+    fn eval(&self, _hit: &RayHit, _wo: &Vector3, _wi: &Vector3) -> Color {
+        Color::black()
+    }
+
+    /// Solid angle density with which [`Material::scatter`] picks `wi`,
+    /// zero when the material cannot be evaluated
+    fn pdf(&self, _hit: &RayHit, _wo: &Vector3, _wi: &Vector3) -> f64 {
+        0.
+    }
+
+    /// Samples a direction for the unit `wo` towards the viewer, `uc` picks
+    /// a lobe and `u` a direction within it. By default the direction comes
+    /// from [`Material::scatter`], losing any change it makes to the ray
+    /// origin, and is evaluated with [`Material::eval`] and [`Material::pdf`]
+    /// when the density is known, or handled as specular otherwise.
+    fn sample(&self, hit: &RayHit, wo: &Vector3, _uc: f64, _u: (f64, f64)) -> Option<BsdfSample> {
+        let mut ray = Ray::of(hit.point + hit.distance * *wo, -*wo);
+        if let Some(wavelength) = hit.wavelength {
+            ray = ray.with_wavelength(wavelength);
+        }
+        let scatter = self.scatter(&ray, hit)?;
+        let wi = scatter.ray.direction().normalize();
+        let flags = if Vector3::dot(&wi, &hit.normal) > 0. {
+            BsdfFlags::REFLECTION
+        } else {
+            BsdfFlags::TRANSMISSION
+        };
+        let pdf = self.pdf(hit, wo, &wi);
+        if pdf > 0. {
+            return Some(BsdfSample {
+                wi,
+                f: self.eval(hit, wo, &wi),
+                pdf,
+                flags,
+            });
+        }
+        BsdfSample::specular(wi, scatter.attenuation, 1., flags, hit)
+    }
+
+    /// Kinds of scattering the material may produce at `hit`, light from
+    /// behind the surface is only evaluated for transmissive materials
+    fn flags(&self, _hit: &RayHit) -> BsdfFlags {
+        BsdfFlags::REFLECTION
+    }
+
+    /// Whether scattering depends on the ray's wavelength, in which case
+    /// spectral paths can only follow a single wavelength afterwards
+    fn dispersive(&self) -> bool {
+        false
+    }
+
+    /// Whether the surface is cut away at `hit`, letting rays pass through
+    fn masked(&self, _hit: &RayHit) -> bool {
+        false
+    }
+
+    /// Flat color stylized passes show where no light reaches, unlike
+    /// [`Material::emitted`] it lights nothing else
+    fn ambient(&self, _hit: &RayHit) -> Color {
+        Color::black()
+    }
 }
 
 pub struct Scatter {
     pub attenuation: Color,
     pub ray: Ray,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::vec3;
+
+    /// Material only implementing [`Material::scatter`], as a mirror
+    struct Mirror;
+
+    impl Material for Mirror {
+        fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+            Some(Scatter {
+                attenuation: Color::new(0.5, 0.25, 1),
+                ray: Ray::of(hit.point, ray.direction().reflect(&hit.normal)),
+            })
+        }
+    }
+
+    #[test]
+    fn default_sample_is_specular_with_the_scatter_weight() {
+        let ray = Ray::of(vec3!(-1, 1, 0), vec3!(1, -1, 0));
+        let hit = RayHit::new(&ray, 1., Vector3::up(), Arc::new(Mirror));
+        let wo = -ray.direction().normalize();
+        let sample = Mirror.sample(&hit, &wo, 0.5, (0.5, 0.5)).unwrap();
+        assert!(sample.flags.is_specular() && !sample.flags.is_transmission());
+        assert!((sample.wi - vec3!(1, 1, 0).normalize()).is_near_zero());
+        assert_eq!(sample.weight(&hit).to_array(), [0.5, 0.25, 1.]);
+    }
+}
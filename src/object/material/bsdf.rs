@@ -0,0 +1,217 @@
+use std::{f64::consts::PI, ops::BitOr};
+
+use crate::{
+    object::geometry::vector::Vector3,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{color::Color, Scatter};
+
+/// Kind of scattering a [`BsdfSample`] comes from
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BsdfFlags(u8);
+
+impl BsdfFlags {
+    pub const REFLECTION: Self = Self(1);
+    pub const TRANSMISSION: Self = Self(1 << 1);
+    pub const DIFFUSE: Self = Self(1 << 2);
+    pub const GLOSSY: Self = Self(1 << 3);
+    /// Delta distribution that can only be found by sampling it
+    pub const SPECULAR: Self = Self(1 << 4);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_specular(&self) -> bool {
+        self.contains(Self::SPECULAR)
+    }
+
+    pub fn is_transmission(&self) -> bool {
+        self.contains(Self::TRANSMISSION)
+    }
+}
+
+impl BitOr for BsdfFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Direction sampled from a material, with the BSDF value and density for it
+///
+/// For specular samples `pdf` is the probability of the chosen lobe and `f`
+/// includes the inverse cosine, so [`BsdfSample::weight`] holds for all kinds.
+#[derive(Debug, Copy, Clone)]
+pub struct BsdfSample {
+    /// Unit world space direction towards the light
+    pub wi: Vector3,
+    pub f: Color,
+    pub pdf: f64,
+    pub flags: BsdfFlags,
+}
+
+impl BsdfSample {
+    /// Specular sample whose path weight is `weight`
+    pub fn specular(
+        wi: Vector3,
+        weight: Color,
+        probability: f64,
+        flags: BsdfFlags,
+        hit: &RayHit,
+    ) -> Option<Self> {
+        let cos_theta = Vector3::dot(&wi, &hit.normal).abs();
+        if cos_theta < 1e-8 || probability <= 0. {
+            return None;
+        }
+        Some(Self {
+            wi,
+            f: weight * probability / cos_theta,
+            pdf: probability,
+            flags: flags | BsdfFlags::SPECULAR,
+        })
+    }
+
+    /// Path throughput factor `f |cos θ| / pdf`
+    pub fn weight(&self, hit: &RayHit) -> Color {
+        if self.pdf <= 0. {
+            return Color::black();
+        }
+        self.f * (Vector3::dot(&self.wi, &hit.normal).abs() / self.pdf)
+    }
+
+    pub fn into_scatter(self, hit: &RayHit) -> Scatter {
+        let mut ray = Ray::of(hit.point, self.wi);
+        if let Some(wavelength) = hit.wavelength {
+            ray = ray.with_wavelength(wavelength);
+        }
+        Scatter {
+            attenuation: self.weight(hit),
+            ray,
+        }
+    }
+}
+
+/// Cosine weighted direction around `z` from two uniform numbers
+pub fn sample_cosine_hemisphere(u: (f64, f64)) -> Vector3 {
+    let radius = u.0.sqrt();
+    let phi = 2. * PI * u.1;
+    Vector3::new(
+        radius * phi.cos(),
+        radius * phi.sin(),
+        (1. - u.0).max(0.).sqrt(),
+    )
+}
+
+/// Checks shared by the material tests
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use crate::{
+        object::{
+            geometry::vector::Vector3,
+            material::{color::Color, Material},
+        },
+        view::ray::{Ray, RayHit},
+    };
+
+    /// Point `index` of the additive recurrence of `alphas`, deterministic
+    /// and evenly spread so estimates do not depend on a random seed
+    fn recurrence<const N: usize>(index: usize, alphas: [f64; N]) -> [f64; N] {
+        alphas.map(|alpha| (0.5 + alpha * index as f64).fract())
+    }
+
+    /// Averages over many directions of a material lit from `theta` radians
+    /// off its normal
+    pub struct Estimate {
+        /// Mean `f·cos/pdf` of the samples that are not specular
+        pub sampled: Color,
+        /// Mean path weight of the specular samples
+        pub specular: Color,
+        /// `∫ f·|cos|` over the sphere, integrated uniformly
+        pub integral: Color,
+        /// `∫ pdf` over the sphere, integrated uniformly
+        pub pdf_integral: f64,
+    }
+
+    impl Estimate {
+        /// Fraction of the light scattered, one for a white furnace
+        pub fn albedo(&self) -> Color {
+            self.sampled + self.specular
+        }
+    }
+
+    /// Samples `material` from the front, or from `behind`, asserting that
+    /// every non specular sample agrees with [`Material::eval`] and
+    /// [`Material::pdf`]
+    pub fn estimate(material: Arc<dyn Material>, theta: f64, behind: bool) -> Estimate {
+        const SAMPLES: usize = 40_000;
+        let direction = Vector3::new(theta.sin(), 0, -theta.cos());
+        let normal = if behind {
+            Vector3::new(0, 0, -1)
+        } else {
+            Vector3::new(0, 0, 1)
+        };
+        let hit = RayHit::new(
+            &Ray::of(Vector3::zero(), direction),
+            1.,
+            normal,
+            material.clone(),
+        );
+        let wo = -direction;
+
+        let (mut sampled, mut specular) = (Color::black(), Color::black());
+        for index in 0..SAMPLES {
+            let [uc, u0, u1] = recurrence(index, [0.819172513, 0.671043606, 0.549700477]);
+            let Some(sample) = material.sample(&hit, &wo, uc, (u0, u1)) else {
+                continue;
+            };
+            if sample.flags.is_specular() {
+                specular += sample.weight(&hit);
+                continue;
+            }
+            let (f, pdf) = (
+                material.eval(&hit, &wo, &sample.wi),
+                material.pdf(&hit, &wo, &sample.wi),
+            );
+            assert!(
+                (f - sample.f).magnitude() <= 1e-9 * (1. + f.magnitude()),
+                "sampled f {:?} but eval {f:?}",
+                sample.f
+            );
+            assert!(
+                (pdf - sample.pdf).abs() <= 1e-9 * (1. + pdf),
+                "sampled pdf {} but pdf {pdf}",
+                sample.pdf
+            );
+            sampled += sample.weight(&hit);
+        }
+
+        let (mut integral, mut pdf_integral) = (Color::black(), 0.);
+        for index in 0..SAMPLES {
+            let [u0, u1] = recurrence(index, [0.754877666, 0.569840291]);
+            let z = 1. - 2. * u0;
+            let radius = (1. - z * z).max(0.).sqrt();
+            let phi = 2. * PI * u1;
+            let wi = Vector3::new(radius * phi.cos(), radius * phi.sin(), z);
+            integral += material.eval(&hit, &wo, &wi) * (4. * PI * z.abs());
+            pdf_integral += material.pdf(&hit, &wo, &wi) * 4. * PI;
+        }
+
+        let samples = SAMPLES as f64;
+        Estimate {
+            sampled: sampled / samples,
+            specular: specular / samples,
+            integral: integral / samples,
+            pdf_integral: pdf_integral / samples,
+        }
+    }
+
+    /// Whether every channel of `a` is within `tolerance` of `b`
+    pub fn near(a: Color, b: Color, tolerance: f64) -> bool {
+        (a - b).to_array().iter().all(|x| x.abs() <= tolerance)
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    fresnel, Material, Scatter,
+};
+
+/// Smooth dielectric clearcoat over a base material, treated as infinitely
+/// thin so light reaching the base keeps its direction
+pub struct Coated {
+    base: Arc<dyn Material>,
+    /// Index of refraction of the coating
+    index: f64,
+}
+
+impl Coated {
+    pub fn new(base: Arc<dyn Material>, index: f64) -> Self {
+        Self { base, index }
+    }
+
+    /// Share of light crossing the coating along the unit `direction`
+    fn transmittance(&self, hit: &RayHit, direction: &Vector3) -> f64 {
+        let cos_theta = Vector3::dot(direction, &hit.normal);
+        if cos_theta > 0. {
+            1. - fresnel::dielectric(cos_theta, self.index)
+        } else {
+            1.
+        }
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        if !hit.front_face {
+            return self.base.scatter(ray, hit);
+        }
+
+        let direction = ray.direction().normalize();
+        let cos_theta = Vector3::dot(&-direction, &hit.normal);
+        if Random::f64() < fresnel::dielectric(cos_theta, self.index) {
+            return Some(Scatter {
+                attenuation: Color::white(),
+                ray: Ray::of(hit.point, direction.reflect(&hit.normal)),
+            });
+        }
+
+        // ? Light scattered by the base is partly reflected back by the
+        // coating on its way out, which this drops instead of tracing
+        let scatter = self.base.scatter(ray, hit)?;
+        let transmittance = self.transmittance(hit, &scatter.ray.direction().normalize());
+        Some(Scatter {
+            attenuation: scatter.attenuation * transmittance,
+            ray: scatter.ray,
+        })
+    }
+
+    /// The base seen through the coating, which leaves the share of light
+    /// it does not reflect on the way in and out
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let base = self.base.eval(hit, wo, wi);
+        if !hit.front_face {
+            return base;
+        }
+        base * (self.transmittance(hit, wo) * self.transmittance(hit, wi))
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        let base = self.base.pdf(hit, wo, wi);
+        if !hit.front_face {
+            return base;
+        }
+        base * self.transmittance(hit, wo)
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        if !hit.front_face {
+            return self.base.sample(hit, wo, uc, u);
+        }
+
+        let reflectance = 1. - self.transmittance(hit, wo);
+        if uc < reflectance {
+            return BsdfSample::specular(
+                (-*wo).reflect(&hit.normal),
+                Color::white(),
+                reflectance,
+                BsdfFlags::REFLECTION,
+                hit,
+            );
+        }
+        let uc = (uc - reflectance) / (1. - reflectance);
+        let sample = self.base.sample(hit, wo, uc, u)?;
+        let transmittance = 1. - reflectance;
+        Some(BsdfSample {
+            f: sample.f * (transmittance * self.transmittance(hit, &sample.wi)),
+            pdf: sample.pdf * transmittance,
+            ..sample
+        })
+    }
+
+    fn flags(&self, hit: &RayHit) -> BsdfFlags {
+        self.base.flags(hit)
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        self.base.emitted(hit)
+    }
+
+    fn dispersive(&self) -> bool {
+        self.base.dispersive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::{
+        bsdf::testing::{estimate, near},
+        lambertian::Lambertian,
+    };
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        for theta in [0.3, 1.3] {
+            let coated = Coated::new(Arc::new(Lambertian::new(Color::new(0.2, 0.5, 0.8))), 1.5);
+            let estimate = estimate(Arc::new(coated), theta, false);
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+            assert!(estimate.pdf_integral <= 1.01, "{}", estimate.pdf_integral);
+        }
+    }
+
+    #[test]
+    fn coating_reflects_its_fresnel_and_darkens_the_base() {
+        let theta: f64 = 1.2;
+        let coated = Coated::new(Arc::new(Lambertian::new(Color::white())), 1.5);
+        let estimate = estimate(Arc::new(coated), theta, false);
+        let reflectance = fresnel::dielectric(theta.cos(), 1.5);
+        assert!(near(estimate.specular, Color::white() * reflectance, 0.01));
+        // ? Light leaving the base loses what the coating reflects back
+        let albedo = estimate.albedo();
+        assert!(albedo.x() < 1. && albedo.x() > 0.85, "{albedo:?}");
+    }
+}
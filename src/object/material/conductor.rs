@@ -0,0 +1,180 @@
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    fresnel::{self, Complex},
+    microfacet::TrowbridgeReitz,
+    Material, Scatter,
+};
+
+/// Rough metal with a GGX microfacet distribution and complex Fresnel
+pub struct Conductor {
+    /// Real part of the index of refraction per channel
+    eta: Color,
+    /// Absorption coefficient per channel
+    k: Color,
+    distribution: TrowbridgeReitz,
+}
+
+impl Conductor {
+    /// `roughness` is perceptual and given along the tangent and bitangent
+    pub fn new(eta: Color, k: Color, roughness: (f64, f64)) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: TrowbridgeReitz::from_roughness(roughness.0, roughness.1),
+        }
+    }
+
+    pub fn isotropic(eta: Color, k: Color, roughness: f64) -> Self {
+        Self::new(eta, k, (roughness, roughness))
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::isotropic(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.386, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::isotropic(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::isotropic(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::isotropic(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    pub fn fresnel(&self, cos_theta: f64) -> Color {
+        let mut reflectance = Color::zero();
+        for i in 0..3 {
+            reflectance[i] = fresnel::complex(cos_theta, Complex::new(self.eta[i], self.k[i]));
+        }
+        reflectance
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, _uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let frame = hit.shading_frame();
+        let wo_local = frame.to_local(wo);
+        if wo_local.z() <= 0. {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            let wi = Vector3::new(-wo_local.x(), -wo_local.y(), wo_local.z());
+            return BsdfSample::specular(
+                frame.local(&wi),
+                self.fresnel(wo_local.z()),
+                1.,
+                BsdfFlags::REFLECTION,
+                hit,
+            );
+        }
+
+        let wm = self.distribution.sample_visible(&wo_local, u);
+        let wi = (-wo_local).reflect(&wm);
+        if wi.z() <= 0. {
+            return None;
+        }
+        let wi = frame.local(&wi);
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf: self.pdf(hit, wo, &wi),
+            flags: BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
+        })
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let frame = hit.shading_frame();
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if self.distribution.is_smooth() || wo.z() <= 0. || wi.z() <= 0. {
+            return Color::black();
+        }
+        let wm = (wo + wi).normalize();
+        let d = self.distribution.d(&wm);
+        let g = self.distribution.g(&wo, &wi);
+        self.fresnel(Vector3::dot(&wo, &wm)) * (d * g / (4. * wo.z() * wi.z()))
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        let frame = hit.shading_frame();
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if self.distribution.is_smooth() || wo.z() <= 0. || wi.z() <= 0. {
+            return 0.;
+        }
+        // ? Reflection maps the visible normal density onto `wi`
+        let wm = (wo + wi).normalize();
+        self.distribution.visible_d(&wo, &wm) / (4. * Vector3::dot(&wo, &wm).abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        for theta in [0.2, 1.1] {
+            let estimate = estimate(Arc::new(Conductor::gold(0.5)), theta, false);
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+            assert!(estimate.pdf_integral <= 1.02, "{}", estimate.pdf_integral);
+        }
+    }
+
+    #[test]
+    fn smooth_metal_reflects_its_fresnel() {
+        let silver = Conductor::silver(0.);
+        let expected = silver.fresnel(0.3_f64.cos());
+        let estimate = estimate(Arc::new(silver), 0.3, false);
+        assert!(near(estimate.specular, expected, 1e-9));
+    }
+
+    #[test]
+    fn perfect_reflector_passes_the_white_furnace() {
+        let mirror = Conductor::isotropic(Color::black(), Color::white() * 1e4, 0.2);
+        let albedo = estimate(Arc::new(mirror), 0.5, false).albedo();
+        assert!(
+            albedo.to_array().iter().all(|&x| x > 0.97 && x <= 1.01),
+            "{albedo:?}"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use crate::{
+    object::{geometry::vector::Vector3, texture::Texture},
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
+
+/// Removes the parts of a surface where the opacity texture falls below a
+/// threshold, for foliage cards and fences
+pub struct Cutout {
+    base: Arc<dyn Material>,
+    /// Opacity read from the texture's channel average
+    alpha: Arc<dyn Texture>,
+    threshold: f64,
+}
+
+impl Cutout {
+    pub fn new(base: Arc<dyn Material>, alpha: Arc<dyn Texture>, threshold: f64) -> Self {
+        Self {
+            base,
+            alpha,
+            threshold,
+        }
+    }
+}
+
+impl Material for Cutout {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        self.base.scatter(ray, hit)
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        self.base.emitted(hit)
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        self.base.eval(hit, wo, wi)
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        self.base.pdf(hit, wo, wi)
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        self.base.sample(hit, wo, uc, u)
+    }
+
+    fn flags(&self, hit: &RayHit) -> BsdfFlags {
+        self.base.flags(hit)
+    }
+
+    fn dispersive(&self) -> bool {
+        self.base.dispersive()
+    }
+
+    fn masked(&self, hit: &RayHit) -> bool {
+        self.alpha.scalar(hit.uv.0, hit.uv.1, &hit.point) < self.threshold || self.base.masked(hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{geometry::sphere::Sphere, material::lambertian::Lambertian, texture::SolidColor},
+        vec3,
+        view::ray::{Hit, HitTarget},
+    };
+
+    /// Opaque only behind the `z = 0` plane
+    struct BackHalf;
+
+    impl Texture for BackHalf {
+        fn value(&self, _: f64, _: f64, point: &Vector3) -> Color {
+            if point.z() < 0. {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        }
+    }
+
+    fn ball(center: Vector3, alpha: Arc<dyn Texture>) -> Arc<Sphere> {
+        let base = Arc::new(Lambertian::new(Color::white()));
+        Arc::new(Sphere::new(
+            center,
+            1.,
+            Arc::new(Cutout::new(base, alpha, 0.5)),
+        ))
+    }
+
+    #[test]
+    fn rays_pass_through_cut_away_parts() {
+        let mut world = HitTarget::new();
+        world.push(ball(Vector3::zero(), Arc::new(BackHalf)));
+        let ray = Ray::of(vec3!(0, 0, 4), vec3!(0, 0, -1));
+        let hit = world.hit(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert!((hit.t - 5.).abs() < 1e-9, "{}", hit.t);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn fully_cut_away_objects_reveal_what_is_behind() {
+        let mut world = HitTarget::new();
+        world.push(ball(
+            Vector3::zero(),
+            Arc::new(SolidColor::new(Color::black())),
+        ));
+        world.push(ball(
+            vec3!(0, 0, -3),
+            Arc::new(SolidColor::new(Color::white())),
+        ));
+        let ray = Ray::of(vec3!(0, 0, 4), vec3!(0, 0, -1));
+        let (index, hit) = world.hit_indexed(&ray, (0.001, f64::INFINITY)).unwrap();
+        assert_eq!(index, 1);
+        assert!((hit.t - 6.).abs() < 1e-9, "{}", hit.t);
+    }
+}
@@ -4,16 +4,108 @@ use crate::{
     view::ray::{Ray, RayHit},
 };
 
-use super::{Material, Scatter};
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    Material, Scatter,
+};
+
+/// Index of refraction, optionally varying with wavelength
+#[derive(Debug, Copy, Clone)]
+pub enum Ior {
+    Constant(f64),
+    /// `n = a + b / λ²` with `λ` in micrometers
+    Cauchy {
+        a: f64,
+        b: f64,
+    },
+    /// `n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)` with `λ` in micrometers
+    Sellmeier {
+        b: [f64; 3],
+        c: [f64; 3],
+    },
+}
+
+impl Ior {
+    /// Wavelength used when the ray carries none, the sodium D line
+    pub const REFERENCE_WAVELENGTH: f64 = 589.3;
+
+    pub fn bk7() -> Self {
+        Ior::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        }
+    }
+
+    pub fn diamond() -> Self {
+        Ior::Sellmeier {
+            b: [0.3306, 4.3356, 0.],
+            c: [0.030625, 0.011236, 0.],
+        }
+    }
+
+    /// Index at `wavelength` in nanometers
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let micrometers = wavelength.unwrap_or(Self::REFERENCE_WAVELENGTH) / 1000.;
+        let squared = micrometers * micrometers;
+        match *self {
+            Ior::Constant(index) => index,
+            Ior::Cauchy { a, b } => a + b / squared,
+            Ior::Sellmeier { b, c } => (1.
+                + (0..3)
+                    .map(|i| b[i] * squared / (squared - c[i]))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, Ior::Constant(_))
+    }
+}
 
 pub struct Dielectric {
     /// Index of refraction
-    index: f64,
+    index: Ior,
+    /// Beer–Lambert absorption coefficient per unit length
+    absorption: Color,
 }
 
 impl Dielectric {
     pub fn new(index: f64) -> Self {
-        Self { index }
+        Self::dispersive(Ior::Constant(index))
+    }
+
+    pub fn dispersive(index: Ior) -> Self {
+        Self {
+            index,
+            absorption: Color::zero(),
+        }
+    }
+
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Absorption such that light keeps `color` after travelling `distance` inside
+    pub fn with_transmission_color(self, color: Color, distance: f64) -> Self {
+        let mut absorption = Color::zero();
+        for i in 0..3 {
+            absorption[i] = -color[i].max(1e-6).ln() / distance;
+        }
+        self.with_absorption(absorption)
+    }
+
+    /// Transmittance along the segment travelled to reach `hit`
+    fn transmittance(&self, hit: &RayHit) -> Color {
+        if hit.front_face {
+            return Color::white();
+        }
+        let mut transmittance = Color::zero();
+        for i in 0..3 {
+            transmittance[i] = (-self.absorption[i] * hit.distance).exp();
+        }
+        transmittance
     }
 
     fn reflectance(&self, cosine: f64, refraction_index: f64) -> f64 {
@@ -25,26 +117,110 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
-        let attenuation = Color::white();
-        let refraction_ratio = if hit.front_face {
-            1. / self.index
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (0., 0.))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, _u: (f64, f64)) -> Option<BsdfSample> {
+        // ? Hitting the back face means the ray just crossed the medium
+        let attenuation = self.transmittance(hit);
+        let index = self.index.at(hit.wavelength);
+        let refraction_ratio = if hit.front_face { 1. / index } else { index };
+        let direction = -*wo;
+        let cos_theta = f64::min(Vector3::dot(wo, &hit.normal), 1.);
+        let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+        let reflect_probability = if refraction_ratio * sin_theta > 1. {
+            1.
         } else {
-            self.index
+            self.reflectance(cos_theta, refraction_ratio)
+        };
+        if uc < reflect_probability {
+            BsdfSample::specular(
+                direction.reflect(&hit.normal),
+                attenuation,
+                reflect_probability,
+                BsdfFlags::REFLECTION,
+                hit,
+            )
+        } else {
+            BsdfSample::specular(
+                direction.refract(&hit.normal, refraction_ratio),
+                attenuation,
+                1. - reflect_probability,
+                BsdfFlags::TRANSMISSION,
+                hit,
+            )
+        }
+    }
+
+    fn dispersive(&self) -> bool {
+        self.index.is_dispersive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        object::material::bsdf::testing::{estimate, near},
+        vec3,
+    };
+
+    #[test]
+    fn absorbs_along_the_path_inside() {
+        let tint = Color::new(0.8, 0.5, 0.2);
+        let glass = Arc::new(Dielectric::new(1.5).with_transmission_color(tint, 2.));
+        let weight = |direction: Vector3, t: f64| {
+            let ray = Ray::of(Vector3::zero(), direction);
+            let hit = RayHit::new(&ray, t, vec3!(0, 0, 1), glass.clone());
+            let sample = glass.sample(&hit, &-direction, 0.99, (0., 0.)).unwrap();
+            sample.weight(&hit)
+        };
+
+        // ? Leaving through the back face after 2 units keeps the tint,
+        // twice as far keeps its square, and entering keeps everything
+        assert!((weight(vec3!(0, 0, 1), 2.) - tint).is_near_zero());
+        assert!((weight(vec3!(0, 0, 1), 4.) - tint * tint).is_near_zero());
+        assert!((weight(vec3!(0, 0, -1), 2.) - Color::white()).is_near_zero());
+    }
+
+    #[test]
+    fn sellmeier_matches_catalog_indices() {
+        assert!((Ior::bk7().at(None) - 1.5168).abs() < 1e-3);
+        assert!((Ior::diamond().at(None) - 2.417).abs() < 5e-3);
+        assert_eq!(Ior::Constant(1.5).at(Some(400.)), 1.5);
+    }
+
+    #[test]
+    fn blue_bends_more_than_red() {
+        let glass = Arc::new(Dielectric::dispersive(Ior::bk7()));
+        let direction = vec3!(1, 0, -1).normalize();
+        let refracted = |wavelength: f64| {
+            let ray = Ray::of(vec3!(-1, 0, 1), direction).with_wavelength(wavelength);
+            let hit = RayHit::new(&ray, 2_f64.sqrt(), vec3!(0, 0, 1), glass.clone());
+            let sample = glass.sample(&hit, &-direction, 0.99, (0., 0.)).unwrap();
+            sample.wi.x()
         };
-        let normalized_ray_direction = ray.direction().normalize();
-        let cos_theta = f64::min(Vector3::dot(&-normalized_ray_direction, &hit.normal), 1.);
-        let sin_theta = (1. - cos_theta).sqrt();
-        let cannot_refract = refraction_ratio * sin_theta > 1.;
-        let scattered_direction =
-            if cannot_refract || self.reflectance(cos_theta, refraction_ratio) > Random::f64() {
-                normalized_ray_direction.reflect(&hit.normal)
-            } else {
-                normalized_ray_direction.refract(&hit.normal, refraction_ratio)
-            };
-
-        Some(Scatter {
-            attenuation,
-            ray: Ray::of(hit.point, scattered_direction),
-        })
+
+        // ? The sideways component shrinks as the index grows
+        assert!(glass.dispersive());
+        assert!(refracted(450.) < refracted(650.));
+        assert!(Ior::bk7().at(Some(450.)) > Ior::bk7().at(Some(650.)));
+    }
+
+    #[test]
+    fn clear_glass_passes_the_white_furnace() {
+        for behind in [false, true] {
+            let estimate = estimate(Arc::new(Dielectric::new(1.5)), 0.6, behind);
+            assert!(estimate.sampled.is_near_zero());
+            assert!(
+                near(estimate.specular, Color::white(), 1e-9),
+                "{:?}",
+                estimate.specular
+            );
+        }
     }
 }
@@ -0,0 +1,39 @@
+use crate::view::ray::{Ray, RayHit};
+
+use super::{color::Color, Material, Scatter};
+
+/// Emits constant radiance and absorbs everything that hits it
+pub struct DiffuseLight {
+    emit: Color,
+    two_sided: bool,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self {
+            emit,
+            two_sided: false,
+        }
+    }
+
+    pub fn two_sided(emit: Color) -> Self {
+        Self {
+            emit,
+            two_sided: true,
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _: &Ray, _: &RayHit) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        if hit.front_face || self.two_sided {
+            self.emit
+        } else {
+            Color::black()
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn real(re: f64) -> Self {
+        Self { re, im: 0. }
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    pub fn sqrt(&self) -> Self {
+        let magnitude = self.norm().sqrt();
+        if magnitude == 0. {
+            return Self::real(0.);
+        }
+        let re = ((magnitude + self.re) / 2.).sqrt();
+        let im = ((magnitude - self.re) / 2.).sqrt();
+        Self::new(re, if self.im < 0. { -im } else { im })
+    }
+
+    pub fn exp(&self) -> Self {
+        let (sin, cos) = self.im.sin_cos();
+        let magnitude = self.re.exp();
+        Self::new(magnitude * cos, magnitude * sin)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let scale = 1. / rhs.norm();
+        Self::new(
+            scale * (self.re * rhs.re + self.im * rhs.im),
+            scale * (self.im * rhs.re - self.re * rhs.im),
+        )
+    }
+}
+
+/// Unpolarized Fresnel reflectance of an interface with relative index `eta`,
+/// a negative cosine means the light arrives from inside
+pub fn dielectric(mut cos_theta_i: f64, mut eta: f64) -> f64 {
+    cos_theta_i = cos_theta_i.clamp(-1., 1.);
+    if cos_theta_i < 0. {
+        eta = 1. / eta;
+        cos_theta_i = -cos_theta_i;
+    }
+
+    let sin2_theta_t = (1. - cos_theta_i * cos_theta_i) / (eta * eta);
+    if sin2_theta_t >= 1. {
+        return 1.;
+    }
+    let cos_theta_t = (1. - sin2_theta_t).max(0.).sqrt();
+
+    let parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.
+}
+
+/// Unpolarized Fresnel reflectance of a conductor with complex index `eta`
+pub fn complex(cos_theta_i: f64, eta: Complex) -> f64 {
+    let cos_theta_i = Complex::real(cos_theta_i.clamp(0., 1.));
+    let sin2_theta_i = Complex::real(1.) - cos_theta_i * cos_theta_i;
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    let cos_theta_t = (Complex::real(1.) - sin2_theta_t).sqrt();
+
+    let parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    (parallel.norm() + perpendicular.norm()) / 2.
+}
+
+/// Reflectance of a film of `film_index` and `thickness` over a substrate,
+/// seen from air, including interference between both interfaces (Airy)
+pub fn thin_film(
+    cos_theta_i: f64,
+    film_index: f64,
+    thickness: f64,
+    substrate: Complex,
+    wavelength: f64,
+) -> f64 {
+    let cos_theta_i = cos_theta_i.clamp(0., 1.);
+    let sin2_theta_i = Complex::real(1. - cos_theta_i * cos_theta_i);
+    let cosine = |index: Complex| (Complex::real(1.) - sin2_theta_i / (index * index)).sqrt();
+
+    let (n1, n2, n3) = (Complex::real(1.), Complex::real(film_index), substrate);
+    let (c1, c2, c3) = (Complex::real(cos_theta_i), cosine(n2), cosine(n3));
+
+    // ? Round trip phase difference through the film, as `e^(iδ)`
+    let delta = Complex::real(4. * PI * thickness / wavelength) * n2 * c2;
+    let phase = Complex::new(-delta.im, delta.re).exp();
+
+    let airy = |r12: Complex, r23: Complex| {
+        ((r12 + r23 * phase) / (Complex::real(1.) + r12 * r23 * phase)).norm()
+    };
+    let perpendicular = |ni: Complex, ci: Complex, nj: Complex, cj: Complex| {
+        (ni * ci - nj * cj) / (ni * ci + nj * cj)
+    };
+    let parallel = |ni: Complex, ci: Complex, nj: Complex, cj: Complex| {
+        (nj * ci - ni * cj) / (nj * ci + ni * cj)
+    };
+
+    let s = airy(perpendicular(n1, c1, n2, c2), perpendicular(n2, c2, n3, c3));
+    let p = airy(parallel(n1, c1, n2, c2), parallel(n2, c2, n3, c3));
+    ((s + p) / 2.).clamp(0., 1.)
+}
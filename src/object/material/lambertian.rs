@@ -1,32 +1,110 @@
+use std::{f64::consts::PI, sync::Arc};
+
 use crate::{
-    object::geometry::vector::Vector3,
+    object::{
+        geometry::{onb::Onb, vector::Vector3},
+        texture::{SolidColor, Texture},
+    },
+    util::random::Random,
     view::ray::{Ray, RayHit},
 };
 
-use super::{color::Color, Material, Scatter};
+use super::{
+    bsdf::{sample_cosine_hemisphere, BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
+    vertex_colors: bool,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+        Self::textured(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
+        Self {
+            albedo,
+            vertex_colors: false,
+        }
+    }
+
+    /// Uses the surface vertex colors as albedo, falling back to `albedo`
+    pub fn vertex_colored(albedo: Color) -> Self {
+        Self {
+            vertex_colors: true,
+            ..Self::new(albedo)
+        }
+    }
+
+    fn albedo(&self, hit: &RayHit) -> Color {
+        match hit.color {
+            Some(color) if self.vertex_colors => color,
+            _ => self.albedo.value(hit.uv.0, hit.uv.1, &hit.point),
+        }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: &Ray, hit: &RayHit) -> Option<Scatter> {
-        let mut scatter_direction = hit.normal + Vector3::random_unit();
-        if scatter_direction.is_near_zero() {
-            scatter_direction = hit.normal;
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, _uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = Onb::from_normal(&hit.normal).local(&sample_cosine_hemisphere(u));
+        let cos_theta = Vector3::dot(&wi, &hit.normal);
+        if cos_theta <= 0. {
+            return None;
         }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf: cos_theta / PI,
+            flags: BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION,
+        })
+    }
 
-        let scattered_ray = Ray::of(hit.point, scatter_direction);
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        if Vector3::dot(wo, &hit.normal) <= 0. || Vector3::dot(wi, &hit.normal) <= 0. {
+            return Color::black();
+        }
+        self.albedo(hit) / PI
+    }
 
-        Some(Scatter {
-            attenuation: self.albedo,
-            ray: scattered_ray,
-        })
+    fn pdf(&self, hit: &RayHit, _wo: &Vector3, wi: &Vector3) -> f64 {
+        Vector3::dot(wi, &hit.normal).max(0.) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    #[test]
+    fn passes_the_white_furnace() {
+        for theta in [0., 1.3] {
+            let estimate = estimate(Arc::new(Lambertian::new(Color::white())), theta, false);
+            assert!(near(estimate.sampled, Color::white(), 1e-9));
+            assert!(near(estimate.integral, Color::white(), 0.01));
+            assert!((estimate.pdf_integral - 1.).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn vertex_colors_replace_the_albedo() {
+        let red = Color::new(0.8, 0.1, 0.1);
+        let lambertian = Arc::new(Lambertian::vertex_colored(Color::white()));
+        let ray = Ray::of(Vector3::up(), -Vector3::up());
+        let mut hit = RayHit::new(&ray, 1., Vector3::up(), lambertian.clone());
+        let eval = |hit: &RayHit| lambertian.eval(hit, &Vector3::up(), &Vector3::up()) * PI;
+        assert!(near(eval(&hit), Color::white(), 1e-12));
+        hit.color = Some(red);
+        assert!(near(eval(&hit), red, 1e-12));
     }
 }
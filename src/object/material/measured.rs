@@ -0,0 +1,164 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{sample_cosine_hemisphere, BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
+
+/// Resolution of the MERL tables along θ half, θ difference and φ difference
+pub const MERL_RESOLUTION: [usize; 3] = [90, 90, 180];
+
+/// Isotropic BRDF tabulated in the half and difference angle
+/// parameterization of Rusinkiewicz, as measured by MERL (Matusik et al. 2003)
+pub struct MeasuredBrdf {
+    values: Vec<Color>,
+}
+
+impl MeasuredBrdf {
+    /// `values` are in MERL order, with φ difference varying fastest
+    pub fn new(values: Vec<Color>) -> Self {
+        assert_eq!(
+            values.len(),
+            MERL_RESOLUTION.iter().product::<usize>(),
+            "measured BRDF table has the wrong size"
+        );
+        Self { values }
+    }
+
+    /// BRDF value for directions in a local frame where `z` is the normal
+    pub fn eval_local(&self, wo: &Vector3, wi: &Vector3) -> Color {
+        if wo.z() <= 0. || wi.z() <= 0. {
+            return Color::black();
+        }
+        let half = (*wo + *wi).normalize();
+        let theta_half = half.z().clamp(-1., 1.).acos();
+        let phi_half = half.y().atan2(half.x());
+
+        // ? The difference vector is `wi` in a frame where the half vector is the pole
+        let rotate_z = |v: Vector3, angle: f64| {
+            let (sin, cos) = angle.sin_cos();
+            Vector3::new(cos * v.x() - sin * v.y(), sin * v.x() + cos * v.y(), v.z())
+        };
+        let rotate_y = |v: Vector3, angle: f64| {
+            let (sin, cos) = angle.sin_cos();
+            Vector3::new(cos * v.x() + sin * v.z(), v.y(), -sin * v.x() + cos * v.z())
+        };
+        let difference = rotate_y(rotate_z(*wi, -phi_half), -theta_half);
+        let theta_difference = difference.z().clamp(-1., 1.).acos();
+        let mut phi_difference = difference.y().atan2(difference.x());
+        // ? Reciprocity makes φ difference symmetric around π
+        if phi_difference < 0. {
+            phi_difference += PI;
+        }
+
+        let [half_resolution, difference_resolution, phi_resolution] = MERL_RESOLUTION;
+        let theta_half_index = {
+            // ? θ half is sampled more densely near the specular peak
+            let index = ((theta_half / FRAC_PI_2).sqrt() * half_resolution as f64) as usize;
+            index.min(half_resolution - 1)
+        };
+        let theta_difference_index = ((theta_difference / FRAC_PI_2 * difference_resolution as f64)
+            as usize)
+            .min(difference_resolution - 1);
+        let phi_difference_index =
+            ((phi_difference / PI * phi_resolution as f64) as usize).min(phi_resolution - 1);
+
+        let index = phi_difference_index
+            + phi_resolution * (theta_difference_index + difference_resolution * theta_half_index);
+        self.values[index].clamp_each(0, f64::MAX)
+    }
+}
+
+impl Material for MeasuredBrdf {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    /// Cosine weighted, the table has no closed form to importance sample
+    fn sample(&self, hit: &RayHit, wo: &Vector3, _uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = hit.shading_frame().local(&sample_cosine_hemisphere(u));
+        let pdf = self.pdf(hit, wo, &wi);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf,
+            flags: BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
+        })
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let frame = hit.shading_frame();
+        self.eval_local(&frame.to_local(wo), &frame.to_local(wi))
+    }
+
+    fn pdf(&self, hit: &RayHit, _wo: &Vector3, wi: &Vector3) -> f64 {
+        Vector3::dot(wi, &hit.normal).max(0.) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    fn table(value: impl Fn(usize) -> Color) -> MeasuredBrdf {
+        MeasuredBrdf::new((0..MERL_RESOLUTION.iter().product()).map(value).collect())
+    }
+
+    #[test]
+    fn constant_table_is_lambertian() {
+        let albedo = Color::new(0.8, 0.4, 0.2);
+        let estimate = estimate(Arc::new(table(|_| albedo / PI)), 0.8, false);
+        assert!(
+            near(estimate.integral, albedo, 0.01),
+            "{:?}",
+            estimate.integral
+        );
+        assert!(
+            near(estimate.sampled, albedo, 1e-9),
+            "{:?}",
+            estimate.sampled
+        );
+    }
+
+    #[test]
+    fn lookups_are_reciprocal() {
+        // ? Every entry is distinct, so equal values mean equal bins
+        let brdf = table(|i| Color::white() * i as f64);
+        let directions = [
+            Vector3::new(0.3, 0.1, 0.9).normalize(),
+            Vector3::new(-0.5, 0.4, 0.6).normalize(),
+            Vector3::new(0.1, -0.8, 0.3).normalize(),
+        ];
+        for wo in directions {
+            for wi in directions {
+                let (forward, backward) = (brdf.eval_local(&wo, &wi), brdf.eval_local(&wi, &wo));
+                assert_eq!(forward.x(), backward.x(), "{wo:?} {wi:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn specular_directions_read_the_first_half_angle() {
+        let brdf = table(|i| Color::white() * i as f64);
+        let wo = Vector3::new(0.6, 0., 0.8);
+        let wi = Vector3::new(-0.6, 0., 0.8);
+        let [_, difference_resolution, phi_resolution] = MERL_RESOLUTION;
+        assert!(brdf.eval_local(&wo, &wi).x() < (difference_resolution * phi_resolution) as f64);
+        assert!(brdf.eval_local(&wo, &-wi).is_near_zero());
+    }
+}
@@ -1,9 +1,16 @@
+use std::f64::consts::PI;
+
 use crate::{
     object::geometry::vector::Vector3,
+    util::random::Random,
     view::ray::{Ray, RayHit},
 };
 
-use super::{color::Color, Material, Scatter};
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
 
 pub struct Metal {
     albedo: Color,
@@ -14,22 +21,111 @@ impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Metal {
         Self { albedo, fuzz }
     }
+
+    /// Density of directions produced by offsetting the mirror direction
+    /// with a uniform point of the fuzz ball, i.e. the share of the ball
+    /// volume covered by the cone of directions around `wi`
+    fn fuzz_pdf(&self, reflected: &Vector3, wi: &Vector3) -> f64 {
+        let b = Vector3::dot(wi, reflected);
+        let discriminant = b * b - (1. - self.fuzz * self.fuzz);
+        if discriminant <= 0. {
+            return 0.;
+        }
+        let far = b + discriminant.sqrt();
+        if far <= 0. {
+            return 0.;
+        }
+        let near = (b - discriminant.sqrt()).max(0.);
+        (far.powi(3) - near.powi(3)) / (4. * PI * self.fuzz.powi(3))
+    }
 }
 
 impl Material for Metal {
     fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
-        let reflection_direction = ray.direction().reflect(&hit.normal).normalize();
-        if Vector3::dot(&reflection_direction, &hit.normal) <= 0. {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let cos_theta = Vector3::dot(wi, &hit.normal);
+        if cos_theta <= 0. || Vector3::dot(wo, &hit.normal) <= 0. {
+            return Color::black();
+        }
+        self.pdf(hit, wo, wi) / cos_theta * self.albedo
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        if self.fuzz <= 0. || Vector3::dot(wi, &hit.normal) <= 0. {
+            return 0.;
+        }
+        self.fuzz_pdf(&(-*wo).reflect(&hit.normal).normalize(), wi)
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let reflected = (-*wo).reflect(&hit.normal).normalize();
+        if Vector3::dot(&reflected, &hit.normal) <= 0. {
             return None;
         }
+        if self.fuzz <= 0. {
+            return BsdfSample::specular(
+                reflected,
+                self.albedo,
+                1.,
+                BsdfFlags::SPECULAR | BsdfFlags::REFLECTION,
+                hit,
+            );
+        }
 
-        let reflection = Ray::of(
-            hit.point,
-            reflection_direction + self.fuzz * Vector3::random_in_unit_sphere(),
-        );
-        Some(Scatter {
-            attenuation: self.albedo,
-            ray: reflection,
+        let z = 1. - 2. * u.0;
+        let ring = (1. - z * z).max(0.).sqrt();
+        let phi = 2. * PI * u.1;
+        let offset = uc.cbrt() * Vector3::new(ring * phi.cos(), ring * phi.sin(), z);
+        let wi = (reflected + self.fuzz * offset).normalize();
+        if Vector3::dot(&wi, &hit.normal) <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf: self.pdf(hit, wo, &wi),
+            flags: BsdfFlags::GLOSSY | BsdfFlags::REFLECTION,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    #[test]
+    fn fuzz_density_matches_its_samples() {
+        for (theta, fuzz) in [(0.2, 0.3), (0.9, 0.8)] {
+            let estimate = estimate(Arc::new(Metal::new(Color::white(), fuzz)), theta, false);
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+        }
+        // ? Near the normal no fuzzed direction falls below the horizon
+        let estimate = estimate(Arc::new(Metal::new(Color::white(), 0.3)), 0.1, false);
+        assert!(
+            (estimate.pdf_integral - 1.).abs() < 0.01,
+            "{}",
+            estimate.pdf_integral
+        );
+    }
+
+    #[test]
+    fn smooth_metal_is_a_tinted_mirror() {
+        let albedo = Color::new(0.9, 0.6, 0.3);
+        let estimate = estimate(Arc::new(Metal::new(albedo, 0.)), 0.7, false);
+        assert!(estimate.sampled.is_near_zero());
+        assert!(near(estimate.specular, albedo, 1e-9));
+    }
+}
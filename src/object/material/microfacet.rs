@@ -0,0 +1,101 @@
+use std::f64::consts::PI;
+
+use crate::object::geometry::vector::Vector3;
+
+/// Trowbridge–Reitz (GGX) microfacet distribution in a local frame where
+/// `z` is the macro surface normal and `x` follows the surface tangent
+#[derive(Debug, Copy, Clone)]
+pub struct TrowbridgeReitz {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl TrowbridgeReitz {
+    pub fn new(alpha_x: f64, alpha_y: f64) -> Self {
+        Self {
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+        }
+    }
+
+    /// Perceptual roughness is squared, as in the Disney and glTF models
+    pub fn from_roughness(roughness_x: f64, roughness_y: f64) -> Self {
+        Self::new(roughness_x * roughness_x, roughness_y * roughness_y)
+    }
+
+    /// Below this the surface is handled as a perfect mirror
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < 1e-3
+    }
+
+    pub fn d(&self, wm: &Vector3) -> f64 {
+        let cos2_theta = wm.z() * wm.z();
+        if cos2_theta == 0. {
+            return 0.;
+        }
+        let slope = (wm.x() * wm.x() / (self.alpha_x * self.alpha_x)
+            + wm.y() * wm.y() / (self.alpha_y * self.alpha_y))
+            / cos2_theta;
+        1. / (PI * self.alpha_x * self.alpha_y * cos2_theta * cos2_theta * (1. + slope).powi(2))
+    }
+
+    pub fn lambda(&self, w: &Vector3) -> f64 {
+        let cos2_theta = w.z() * w.z();
+        if cos2_theta == 0. {
+            return f64::INFINITY;
+        }
+        let alpha2_tan2_theta = (w.x() * w.x() * self.alpha_x * self.alpha_x
+            + w.y() * w.y() * self.alpha_y * self.alpha_y)
+            / cos2_theta;
+        ((1. + alpha2_tan2_theta).sqrt() - 1.) / 2.
+    }
+
+    /// Smith masking
+    pub fn g1(&self, w: &Vector3) -> f64 {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// Height-correlated Smith masking-shadowing
+    pub fn g(&self, wo: &Vector3, wi: &Vector3) -> f64 {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Density of normals visible from `w`
+    pub fn visible_d(&self, w: &Vector3, wm: &Vector3) -> f64 {
+        if w.z() == 0. {
+            return 0.;
+        }
+        self.g1(w) / w.z().abs() * self.d(wm) * Vector3::dot(w, wm).abs()
+    }
+
+    /// Samples a normal from the visible normal distribution (Heitz 2018)
+    pub fn sample_visible(&self, w: &Vector3, u: (f64, f64)) -> Vector3 {
+        let mut wh = Vector3::new(self.alpha_x * w.x(), self.alpha_y * w.y(), w.z()).normalize();
+        if wh.z() < 0. {
+            wh = -wh;
+        }
+
+        let t1 = if wh.z() < 0.99999 {
+            Vector3::cross(&Vector3::new(0, 0, 1), &wh).normalize()
+        } else {
+            Vector3::new(1, 0, 0)
+        };
+        let t2 = Vector3::cross(&wh, &t1);
+
+        let radius = u.0.sqrt();
+        let angle = 2. * PI * u.1;
+        let px = radius * angle.cos();
+        let mut py = radius * angle.sin();
+        let h = (1. - px * px).sqrt();
+        py = (1. - (1. + wh.z()) / 2.) * h + (1. + wh.z()) / 2. * py;
+        let pz = (1. - px * px - py * py).max(0.).sqrt();
+
+        let nh = px * t1 + py * t2 + pz * wh;
+        Vector3::new(
+            self.alpha_x * nh.x(),
+            self.alpha_y * nh.y(),
+            nh.z().max(1e-6),
+        )
+        .normalize()
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use crate::{
+    object::{
+        geometry::vector::Vector3,
+        texture::{SolidColor, Texture},
+    },
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
+
+/// Picks `second` with probability `weight` and `first` otherwise
+pub struct MixMaterial {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    weight: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: f64) -> Self {
+        Self::textured(
+            first,
+            second,
+            Arc::new(SolidColor::new(Color::white() * weight)),
+        )
+    }
+
+    /// Weight read from the mask texture's channel average
+    pub fn textured(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        weight: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            weight,
+        }
+    }
+
+    fn weight(&self, hit: &RayHit) -> f64 {
+        self.weight
+            .scalar(hit.uv.0, hit.uv.1, &hit.point)
+            .clamp(0., 1.)
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        if Random::f64() < self.weight(hit) {
+            self.second.scatter(ray, hit)
+        } else {
+            self.first.scatter(ray, hit)
+        }
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        let weight = self.weight(hit);
+        (1. - weight) * self.first.emitted(hit) + weight * self.second.emitted(hit)
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        if self.pdf(hit, wo, wi) == 0. {
+            return Color::black();
+        }
+        let weight = self.weight(hit);
+        (1. - weight) * self.first.eval(hit, wo, wi) + weight * self.second.eval(hit, wo, wi)
+    }
+
+    /// Only defined when both materials can be evaluated, since the
+    /// density of a sample from an unevaluable one is unknown
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        let weight = self.weight(hit);
+        let first = self.first.pdf(hit, wo, wi);
+        let second = self.second.pdf(hit, wo, wi);
+        if (weight < 1. && first == 0.) || (weight > 0. && second == 0.) {
+            return 0.;
+        }
+        (1. - weight) * first + weight * second
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let weight = self.weight(hit);
+        let (material, probability, uc) = if uc < weight {
+            (&self.second, weight, uc / weight)
+        } else {
+            (&self.first, 1. - weight, (uc - weight) / (1. - weight))
+        };
+        let sample = material.sample(hit, wo, uc.min(1. - f64::EPSILON), u)?;
+        let pdf = self.pdf(hit, wo, &sample.wi);
+        if !sample.flags.is_specular() && pdf > 0. {
+            return Some(BsdfSample {
+                f: self.eval(hit, wo, &sample.wi),
+                pdf,
+                ..sample
+            });
+        }
+        // ? Without a density for the mix, the sample is handled as specular
+        // and keeps the weight it had for its material, as with scattering
+        let probability = if sample.flags.is_specular() {
+            sample.pdf * probability
+        } else {
+            probability
+        };
+        BsdfSample::specular(
+            sample.wi,
+            sample.weight(hit),
+            probability,
+            sample.flags,
+            hit,
+        )
+    }
+
+    fn flags(&self, hit: &RayHit) -> BsdfFlags {
+        self.first.flags(hit) | self.second.flags(hit)
+    }
+
+    fn dispersive(&self) -> bool {
+        self.first.dispersive() || self.second.dispersive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::{
+        bsdf::testing::{estimate, near},
+        conductor::Conductor,
+        lambertian::Lambertian,
+        oren_nayar::OrenNayar,
+    };
+
+    #[test]
+    fn blends_both_materials() {
+        let red: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.8, 0., 0.)));
+        let blue: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0., 0., 0.4)));
+        let integral = |weight: f64| {
+            let mix = MixMaterial::new(red.clone(), blue.clone(), weight);
+            estimate(Arc::new(mix), 0.4, false).integral
+        };
+        let expected = |weight: f64| Color::new(0.8 * (1. - weight), 0., 0.4 * weight);
+        for weight in [0., 0.25, 1.] {
+            assert!(near(integral(weight), expected(weight), 0.01));
+        }
+    }
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        let mix = MixMaterial::new(
+            Arc::new(Lambertian::new(Color::new(0.9, 0.6, 0.3))),
+            Arc::new(OrenNayar::new(Color::white(), 30.)),
+            0.3,
+        );
+        let estimate = estimate(Arc::new(mix), 0.9, false);
+        assert!(
+            near(estimate.sampled, estimate.integral, 0.02),
+            "sampled {:?} integral {:?}",
+            estimate.sampled,
+            estimate.integral
+        );
+    }
+
+    #[test]
+    fn mixing_with_a_mirror_passes_the_white_furnace() {
+        let mirror = Conductor::isotropic(Color::black(), Color::white() * 1e4, 0.);
+        let mix = MixMaterial::new(
+            Arc::new(Lambertian::new(Color::white())),
+            Arc::new(mirror),
+            0.5,
+        );
+        // ? The mirror has no density, so diffuse samples keep the weight
+        // they had for the Lambertian and count as specular
+        let estimate = estimate(Arc::new(mix), 0.6, false);
+        assert!(
+            near(estimate.albedo(), Color::white(), 0.01),
+            "{:?}",
+            estimate.albedo()
+        );
+    }
+}
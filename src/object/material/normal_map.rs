@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use crate::{
+    object::{
+        geometry::{onb::Onb, vector::Vector3},
+        texture::Texture,
+    },
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
+
+/// Offset applied along the geometric normal to scattered ray origins
+const SURFACE_OFFSET: f64 = 1e-4;
+/// Finite difference step in uv space for bump maps
+const BUMP_DELTA: f64 = 5e-4;
+
+pub enum Perturbation {
+    /// Tangent space normals encoded as `0.5 * (normal + 1)`
+    NormalMap(Arc<dyn Texture>),
+    /// Scalar heights, scaled into object space
+    BumpMap {
+        texture: Arc<dyn Texture>,
+        scale: f64,
+    },
+}
+
+/// Perturbs the shading normal before handing the hit to another material
+pub struct NormalMapped {
+    base: Arc<dyn Material>,
+    perturbation: Perturbation,
+}
+
+impl NormalMapped {
+    pub fn new(base: Arc<dyn Material>, perturbation: Perturbation) -> Self {
+        Self { base, perturbation }
+    }
+
+    pub fn normal_map(base: Arc<dyn Material>, texture: Arc<dyn Texture>) -> Self {
+        Self::new(base, Perturbation::NormalMap(texture))
+    }
+
+    pub fn bump_map(base: Arc<dyn Material>, texture: Arc<dyn Texture>, scale: f64) -> Self {
+        Self::new(base, Perturbation::BumpMap { texture, scale })
+    }
+
+    fn perturbed_normal(&self, hit: &RayHit) -> Option<Vector3> {
+        if hit.tangent.is_near_zero() {
+            return None;
+        }
+
+        let normal = hit.outward_normal();
+        let (u, v) = hit.uv;
+        let perturbed = match &self.perturbation {
+            Perturbation::NormalMap(texture) => {
+                let frame = Onb::from_normal_tangent(&normal, &hit.tangent);
+                let encoded: Vector3 = 2. * texture.value(u, v, &hit.point) - Vector3::ones();
+                // ? Mirrored uvs flip the bitangent, which glTF stores as
+                // the sign of `tangent.w`
+                let handedness = if Vector3::dot(frame.v(), &hit.bitangent) < 0. {
+                    -1.
+                } else {
+                    1.
+                };
+                frame.local(&Vector3::new(
+                    encoded.x(),
+                    handedness * encoded.y(),
+                    encoded.z(),
+                ))
+            }
+            Perturbation::BumpMap { texture, scale } => {
+                if hit.bitangent.is_near_zero() {
+                    return None;
+                }
+
+                let height = texture.scalar(u, v, &hit.point);
+                let height_u = texture.scalar(u + BUMP_DELTA, v, &hit.point);
+                let height_v = texture.scalar(u, v + BUMP_DELTA, &hit.point);
+                let tangent = hit.tangent + scale * (height_u - height) / BUMP_DELTA * normal;
+                let bitangent = hit.bitangent + scale * (height_v - height) / BUMP_DELTA * normal;
+                let bumped = Vector3::cross(&tangent, &bitangent);
+                if Vector3::dot(&bumped, &normal) < 0. {
+                    -bumped
+                } else {
+                    bumped
+                }
+            }
+        };
+
+        if perturbed.is_near_zero() {
+            None
+        } else {
+            Some(perturbed.normalize())
+        }
+    }
+
+    /// Copy of `hit` whose shading normal is perturbed
+    pub fn perturb(&self, hit: &RayHit) -> RayHit {
+        let mut hit = hit.clone();
+        if let Some(normal) = self.perturbed_normal(&hit) {
+            hit.normal = if hit.front_face { normal } else { -normal };
+        }
+        hit
+    }
+
+    /// Turns a value weighted by the cosine about the perturbed normal into
+    /// one weighted by the cosine about `hit`'s normal, which integrators use
+    fn cosine_ratio(hit: &RayHit, perturbed: &RayHit, wi: &Vector3) -> f64 {
+        let cos_theta = Vector3::dot(wi, &hit.normal).abs();
+        if cos_theta == 0. {
+            return 0.;
+        }
+        Vector3::dot(wi, &perturbed.normal).abs() / cos_theta
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let scatter = self.base.scatter(ray, &self.perturb(hit))?;
+
+        // ? Shading normals can send rays below the actual surface, so the
+        // origin is pushed to whichever side of the geometry they leave from
+        let direction = *scatter.ray.direction();
+        let side = Vector3::dot(&direction, &hit.geometric_normal).signum();
+        let origin = hit.point + side * SURFACE_OFFSET * hit.geometric_normal;
+        Some(Scatter {
+            attenuation: scatter.attenuation,
+            ray: Ray::of(origin, direction),
+        })
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        self.base.emitted(hit)
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let perturbed = self.perturb(hit);
+        self.base.eval(&perturbed, wo, wi) * Self::cosine_ratio(hit, &perturbed, wi)
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        self.base.pdf(&self.perturb(hit), wo, wi)
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let perturbed = self.perturb(hit);
+        let sample = self.base.sample(&perturbed, wo, uc, u)?;
+        if !sample.flags.is_specular() {
+            return Some(BsdfSample {
+                f: sample.f * Self::cosine_ratio(hit, &perturbed, &sample.wi),
+                ..sample
+            });
+        }
+        // ? Specular samples keep the path weight they had about the
+        // perturbed normal
+        BsdfSample::specular(
+            sample.wi,
+            sample.weight(&perturbed),
+            sample.pdf,
+            sample.flags,
+            hit,
+        )
+    }
+
+    fn flags(&self, hit: &RayHit) -> BsdfFlags {
+        self.base.flags(&self.perturb(hit))
+    }
+
+    fn dispersive(&self) -> bool {
+        self.base.dispersive()
+    }
+
+    fn masked(&self, hit: &RayHit) -> bool {
+        self.base.masked(hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{material::lambertian::Lambertian, texture::SolidColor},
+        vec3,
+    };
+
+    fn hit(material: Arc<dyn Material>, bitangent: Vector3) -> RayHit {
+        let ray = Ray::of(vec3!(0, 1, 0), vec3!(0, -1, 0));
+        let mut hit = RayHit::new(&ray, 1., Vector3::up(), material);
+        hit.tangent = vec3!(1, 0, 0);
+        hit.bitangent = bitangent;
+        hit
+    }
+
+    #[test]
+    fn mirrored_uvs_flip_the_bitangent() {
+        // ? Encodes the tangent space normal (0, 0.6, 0.8)
+        let texture = Arc::new(SolidColor::new(Color::new(0.5, 0.8, 0.9)));
+        let mapped = Arc::new(NormalMapped::normal_map(
+            Arc::new(Lambertian::new(Color::white())),
+            texture,
+        ));
+        let normal = |bitangent| mapped.perturb(&hit(mapped.clone(), bitangent)).normal;
+        assert!((normal(vec3!(0, 0, -1)) - vec3!(0, 0.8, -0.6)).is_near_zero());
+        assert!((normal(vec3!(0, 0, 1)) - vec3!(0, 0.8, 0.6)).is_near_zero());
+    }
+
+    #[test]
+    fn eval_is_weighted_by_the_unperturbed_cosine() {
+        let texture = Arc::new(SolidColor::new(Color::new(0.5, 0.8, 0.9)));
+        let mapped = Arc::new(NormalMapped::normal_map(
+            Arc::new(Lambertian::new(Color::white())),
+            texture,
+        ));
+        let hit = hit(mapped.clone(), vec3!(0, 0, -1));
+        let perturbed = mapped.perturb(&hit);
+        let wo = Vector3::up();
+        let wi = vec3!(0.3, 0.5, -0.4).normalize();
+
+        // ? Reflected light is f·cos about the perturbed normal either way
+        let expected =
+            mapped.base.eval(&perturbed, &wo, &wi) * Vector3::dot(&wi, &perturbed.normal).abs();
+        let actual = mapped.eval(&hit, &wo, &wi) * Vector3::dot(&wi, &hit.normal).abs();
+        assert!((expected - actual).is_near_zero());
+
+        let sample = mapped.sample(&hit, &wo, 0.5, (0.3, 0.6)).unwrap();
+        assert!((sample.f - mapped.eval(&hit, &wo, &sample.wi)).is_near_zero());
+        assert!((sample.pdf - mapped.pdf(&hit, &wo, &sample.wi)).abs() < 1e-9);
+    }
+}
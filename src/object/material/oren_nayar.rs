@@ -0,0 +1,141 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    object::{
+        geometry::vector::Vector3,
+        texture::{SolidColor, Texture},
+    },
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{sample_cosine_hemisphere, BsdfFlags, BsdfSample},
+    color::Color,
+    Material, Scatter,
+};
+
+/// Rough diffuse reflection from V-shaped microfacets (Oren and Nayar 1994)
+pub struct OrenNayar {
+    albedo: Arc<dyn Texture>,
+    a: f64,
+    b: f64,
+}
+
+impl OrenNayar {
+    /// `sigma` is the standard deviation of the facet slopes in degrees
+    pub fn new(albedo: Color, sigma: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)), sigma)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, sigma: f64) -> Self {
+        let sigma2 = sigma.to_radians().powi(2);
+        Self {
+            albedo,
+            a: 1. - sigma2 / (2. * (sigma2 + 0.33)),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+}
+
+impl OrenNayar {
+    /// Reflectance relative to a Lambertian surface, for local directions
+    fn scale(&self, wo: &Vector3, wi: &Vector3) -> f64 {
+        let sin_theta_o = (1. - wo.z() * wo.z()).max(0.).sqrt();
+        let sin_theta_i = (1. - wi.z() * wi.z()).max(0.).sqrt();
+        let cos_phi_difference = if sin_theta_o > 1e-4 && sin_theta_i > 1e-4 {
+            (wo.x() * wi.x() + wo.y() * wi.y()) / (sin_theta_o * sin_theta_i)
+        } else {
+            0.
+        };
+
+        // ? Alpha is the larger polar angle and beta the smaller one
+        let (sin_alpha, tan_beta) = if wi.z().abs() > wo.z().abs() {
+            (sin_theta_o, sin_theta_i / wi.z().abs())
+        } else {
+            (sin_theta_i, sin_theta_o / wo.z().abs().max(1e-4))
+        };
+        self.a + self.b * cos_phi_difference.max(0.) * sin_alpha * tan_beta
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, _uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let wi = hit.shading_frame().local(&sample_cosine_hemisphere(u));
+        let pdf = self.pdf(hit, wo, &wi);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf,
+            flags: BsdfFlags::DIFFUSE | BsdfFlags::REFLECTION,
+        })
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let frame = hit.shading_frame();
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z() <= 0. || wi.z() <= 0. {
+            return Color::black();
+        }
+        self.albedo.value(hit.uv.0, hit.uv.1, &hit.point) * self.scale(&wo, &wi) / PI
+    }
+
+    fn pdf(&self, hit: &RayHit, _wo: &Vector3, wi: &Vector3) -> f64 {
+        Vector3::dot(wi, &hit.normal).max(0.) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::{
+        bsdf::testing::{estimate, near},
+        lambertian::Lambertian,
+    };
+
+    #[test]
+    fn smooth_facets_are_lambertian() {
+        let albedo = Color::new(0.7, 0.5, 0.3);
+        let rough = estimate(Arc::new(OrenNayar::new(albedo, 0.)), 0.7, false);
+        let lambertian = estimate(Arc::new(Lambertian::new(albedo)), 0.7, false);
+        assert!(near(rough.sampled, lambertian.sampled, 1e-9));
+        assert!(near(rough.integral, lambertian.integral, 1e-9));
+    }
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        for theta in [0.2, 1.2] {
+            let estimate = estimate(Arc::new(OrenNayar::new(Color::white(), 30.)), theta, false);
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+            assert!((estimate.pdf_integral - 1.).abs() < 0.02);
+            // ? The model ignores interreflections between facets, which
+            // lose light
+            assert!(estimate.sampled.x() < 1., "{:?}", estimate.sampled);
+        }
+    }
+
+    #[test]
+    fn rough_surfaces_scatter_back_towards_the_light() {
+        let material = Arc::new(OrenNayar::new(Color::white(), 40.));
+        let wo = Vector3::new(1, 0, 1).normalize();
+        let ray = Ray::of(Vector3::zero(), -wo);
+        let hit = RayHit::new(&ray, 1., Vector3::new(0, 0, 1), material.clone());
+        let back = material.eval(&hit, &wo, &wo);
+        let forward = material.eval(&hit, &wo, &Vector3::new(-1, 0, 1).normalize());
+        assert!(back.x() > forward.x(), "{back:?} {forward:?}");
+    }
+}
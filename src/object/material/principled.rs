@@ -0,0 +1,517 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    object::{
+        geometry::vector::Vector3,
+        texture::{SolidColor, Texture},
+    },
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{sample_cosine_hemisphere, BsdfFlags, BsdfSample},
+    color::Color,
+    microfacet::TrowbridgeReitz,
+    rough_dielectric::Interface,
+    Material, Scatter,
+};
+
+/// Disney principled BSDF (Burley 2012), with textured base color,
+/// metallic and roughness inputs
+pub struct Principled {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn Texture>,
+    roughness: Arc<dyn Texture>,
+    specular: f64,
+    specular_tint: f64,
+    sheen: f64,
+    sheen_tint: f64,
+    clearcoat: f64,
+    clearcoat_gloss: f64,
+    transmission: f64,
+    /// Index of refraction used by the transmission lobe
+    index: f64,
+    subsurface: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Lobe {
+    Diffuse,
+    Specular,
+    Transmission,
+    Clearcoat,
+}
+
+const LOBES: [Lobe; 4] = [
+    Lobe::Diffuse,
+    Lobe::Specular,
+    Lobe::Transmission,
+    Lobe::Clearcoat,
+];
+
+fn scalar(value: f64) -> Arc<dyn Texture> {
+    Arc::new(SolidColor::new(Color::white() * value))
+}
+
+fn luminance(color: &Color) -> f64 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1. - cos_theta.clamp(0., 1.)).powi(5)
+}
+
+impl Principled {
+    pub fn new(base_color: Color) -> Self {
+        Self::textured(Arc::new(SolidColor::new(base_color)))
+    }
+
+    pub fn textured(base_color: Arc<dyn Texture>) -> Self {
+        Self {
+            base_color,
+            metallic: scalar(0.),
+            roughness: scalar(0.5),
+            specular: 0.5,
+            specular_tint: 0.,
+            sheen: 0.,
+            sheen_tint: 0.5,
+            clearcoat: 0.,
+            clearcoat_gloss: 1.,
+            transmission: 0.,
+            index: 1.5,
+            subsurface: 0.,
+        }
+    }
+
+    pub fn with_metallic(self, metallic: f64) -> Self {
+        self.with_metallic_texture(scalar(metallic))
+    }
+
+    /// Metallic read from the texture's channel average
+    pub fn with_metallic_texture(mut self, metallic: Arc<dyn Texture>) -> Self {
+        self.metallic = metallic;
+        self
+    }
+
+    pub fn with_roughness(self, roughness: f64) -> Self {
+        self.with_roughness_texture(scalar(roughness))
+    }
+
+    /// Perceptual roughness read from the texture's channel average
+    pub fn with_roughness_texture(mut self, roughness: Arc<dyn Texture>) -> Self {
+        self.roughness = roughness;
+        self
+    }
+
+    /// Dielectric specular amount, `0.5` being a reflectance of 4%
+    pub fn with_specular(mut self, specular: f64, specular_tint: f64) -> Self {
+        self.specular = specular;
+        self.specular_tint = specular_tint;
+        self
+    }
+
+    pub fn with_sheen(mut self, sheen: f64, sheen_tint: f64) -> Self {
+        self.sheen = sheen;
+        self.sheen_tint = sheen_tint;
+        self
+    }
+
+    pub fn with_clearcoat(mut self, clearcoat: f64, clearcoat_gloss: f64) -> Self {
+        self.clearcoat = clearcoat;
+        self.clearcoat_gloss = clearcoat_gloss;
+        self
+    }
+
+    pub fn with_transmission(mut self, transmission: f64, index: f64) -> Self {
+        self.transmission = transmission;
+        self.index = index;
+        self
+    }
+
+    /// Blends the diffuse lobe towards the Hanrahan–Krueger approximation
+    pub fn with_subsurface(mut self, subsurface: f64) -> Self {
+        self.subsurface = subsurface;
+        self
+    }
+}
+
+/// [`Principled`] inputs resolved at a hit, for local directions with `wo`
+/// on the `z` side
+struct Surface<'a> {
+    material: &'a Principled,
+    base_color: Color,
+    tint: Color,
+    roughness: f64,
+    metallic: f64,
+    specular_color: Color,
+    distribution: TrowbridgeReitz,
+    clearcoat: TrowbridgeReitz,
+    interface: Interface,
+    /// Probability of sampling each of [`LOBES`]
+    probabilities: [f64; 4],
+}
+
+impl<'a> Surface<'a> {
+    fn new(material: &'a Principled, hit: &RayHit, wo: &Vector3) -> Self {
+        let (u, v) = hit.uv;
+        let base_color = material.base_color.value(u, v, &hit.point);
+        let metallic = material.metallic.scalar(u, v, &hit.point).clamp(0., 1.);
+        let roughness = material.roughness.scalar(u, v, &hit.point).clamp(0., 1.);
+        let tint = match luminance(&base_color) {
+            luminance if luminance > 0. => base_color / luminance,
+            _ => Color::white(),
+        };
+        let specular_color = Vector3::lerp(
+            &(material.specular
+                * 0.08
+                * Vector3::lerp(&Color::white(), &tint, material.specular_tint)),
+            &base_color,
+            metallic,
+        );
+        let distribution = TrowbridgeReitz::from_roughness(roughness, roughness);
+        let alpha = 0.1 + (0.001 - 0.1) * material.clearcoat_gloss;
+        let eta = if hit.front_face {
+            material.index
+        } else {
+            1. / material.index
+        };
+
+        let mut surface = Self {
+            material,
+            base_color,
+            tint,
+            roughness,
+            metallic,
+            specular_color,
+            distribution,
+            clearcoat: TrowbridgeReitz::new(alpha, alpha),
+            interface: Interface { distribution, eta },
+            probabilities: [0.; 4],
+        };
+
+        // ? Lobes are picked in proportion to their expected contribution
+        let weights = [
+            surface.weight(Lobe::Diffuse),
+            surface.weight(Lobe::Specular) * luminance(&surface.specular_fresnel(wo.z())).max(0.05),
+            surface.weight(Lobe::Transmission),
+            surface.weight(Lobe::Clearcoat) * clearcoat_fresnel(wo.z()),
+        ];
+        let total: f64 = weights.iter().sum();
+        surface.probabilities = weights.map(|weight| weight / total);
+        surface
+    }
+
+    /// Factor scaling each lobe in the BSDF
+    fn weight(&self, lobe: Lobe) -> f64 {
+        let material = self.material;
+        match lobe {
+            Lobe::Diffuse => (1. - self.metallic) * (1. - material.transmission),
+            // ? The transmission lobe reflects its share of the light itself
+            Lobe::Specular => 1. - (1. - self.metallic) * material.transmission,
+            Lobe::Transmission => (1. - self.metallic) * material.transmission,
+            Lobe::Clearcoat => 0.25 * material.clearcoat,
+        }
+    }
+
+    fn probability(&self, lobe: Lobe) -> f64 {
+        self.probabilities[lobe as usize]
+    }
+
+    fn specular_fresnel(&self, cos_theta: f64) -> Color {
+        self.specular_color + (Color::white() - self.specular_color) * schlick_weight(cos_theta)
+    }
+
+    /// Whether the lobe is a delta distribution that can only be sampled
+    fn is_smooth(&self, lobe: Lobe) -> bool {
+        match lobe {
+            Lobe::Diffuse => false,
+            Lobe::Specular | Lobe::Transmission => self.distribution.is_smooth(),
+            Lobe::Clearcoat => self.clearcoat.is_smooth(),
+        }
+    }
+
+    /// Unweighted value of a lobe that is not smooth
+    fn lobe_eval(&self, lobe: Lobe, wo: &Vector3, wi: &Vector3) -> Color {
+        if lobe != Lobe::Transmission && wi.z() <= 0. {
+            return Color::black();
+        }
+        match lobe {
+            Lobe::Diffuse => {
+                let material = self.material;
+                let (cos_l, cos_v) = (wi.z(), wo.z());
+                let cos_d = Vector3::dot(wi, &(*wi + *wo).normalize());
+                let (fl, fv) = (schlick_weight(cos_l), schlick_weight(cos_v));
+
+                let fd90 = 0.5 + 2. * cos_d * cos_d * self.roughness;
+                let diffuse = (1. + (fd90 - 1.) * fl) * (1. + (fd90 - 1.) * fv);
+                let fss90 = cos_d * cos_d * self.roughness;
+                let fss = (1. + (fss90 - 1.) * fl) * (1. + (fss90 - 1.) * fv);
+                let subsurface = 1.25 * (fss * (1. / (cos_l + cos_v) - 0.5) + 0.5);
+
+                let sheen_color = Vector3::lerp(&Color::white(), &self.tint, material.sheen_tint);
+                let sheen = material.sheen * schlick_weight(cos_d) * sheen_color;
+                self.base_color * (diffuse + (subsurface - diffuse) * material.subsurface) / PI
+                    + sheen
+            }
+            Lobe::Specular => {
+                let wm = (*wo + *wi).normalize();
+                self.specular_fresnel(Vector3::dot(wo, &wm))
+                    * microfacet_reflection(&self.distribution, wo, wi, &wm)
+            }
+            Lobe::Transmission => {
+                let color = if wi.z() > 0. {
+                    Color::white()
+                } else {
+                    self.base_color
+                };
+                color * self.interface.eval(wo, wi)
+            }
+            Lobe::Clearcoat => {
+                let wm = (*wo + *wi).normalize();
+                Color::white()
+                    * clearcoat_fresnel(Vector3::dot(wo, &wm))
+                    * microfacet_reflection(&self.clearcoat, wo, wi, &wm)
+            }
+        }
+    }
+
+    /// Density with which a lobe that is not smooth samples `wi`
+    fn lobe_pdf(&self, lobe: Lobe, wo: &Vector3, wi: &Vector3) -> f64 {
+        if lobe != Lobe::Transmission && wi.z() <= 0. {
+            return 0.;
+        }
+        match lobe {
+            Lobe::Diffuse => wi.z() / PI,
+            Lobe::Specular => microfacet_reflection_pdf(&self.distribution, wo, wi),
+            Lobe::Transmission => self.interface.pdf(wo, wi),
+            Lobe::Clearcoat => microfacet_reflection_pdf(&self.clearcoat, wo, wi),
+        }
+    }
+
+    fn lobes(&self) -> impl Iterator<Item = Lobe> + '_ {
+        LOBES
+            .into_iter()
+            .filter(|&lobe| self.probability(lobe) > 0. && !self.is_smooth(lobe))
+    }
+
+    fn eval(&self, wo: &Vector3, wi: &Vector3) -> Color {
+        self.lobes().fold(Color::black(), |f, lobe| {
+            f + self.weight(lobe) * self.lobe_eval(lobe, wo, wi)
+        })
+    }
+
+    fn pdf(&self, wo: &Vector3, wi: &Vector3) -> f64 {
+        self.lobes()
+            .map(|lobe| self.probability(lobe) * self.lobe_pdf(lobe, wo, wi))
+            .sum()
+    }
+
+    /// Picks a lobe with `uc` and samples it
+    fn sample(&self, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<LobeSample> {
+        let mut choice = uc;
+        let lobe = LOBES
+            .into_iter()
+            .find(|&lobe| {
+                choice -= self.probability(lobe);
+                choice < 0. && self.probability(lobe) > 0.
+            })
+            .unwrap_or(Lobe::Specular);
+        let probability = self.probability(lobe);
+        // ? What is left of `uc` inside the chosen lobe is uniform again
+        let uc = ((choice + probability) / probability).clamp(0., 1. - f64::EPSILON);
+
+        let smooth = self.is_smooth(lobe);
+        let (wi, specular) = match lobe {
+            Lobe::Diffuse => (sample_cosine_hemisphere(u), None),
+            Lobe::Specular | Lobe::Clearcoat => {
+                let distribution = if lobe == Lobe::Specular {
+                    &self.distribution
+                } else {
+                    &self.clearcoat
+                };
+                let wm = if smooth {
+                    Vector3::new(0, 0, 1)
+                } else {
+                    distribution.sample_visible(wo, u)
+                };
+                let wi = (-*wo).reflect(&wm);
+                let specular = smooth.then(|| {
+                    let fresnel = if lobe == Lobe::Specular {
+                        self.specular_fresnel(wo.z())
+                    } else {
+                        Color::white() * clearcoat_fresnel(wo.z())
+                    };
+                    (fresnel * self.weight(lobe) / probability, probability)
+                });
+                (wi, specular)
+            }
+            Lobe::Transmission => {
+                let (wi, chance) = self.interface.sample(wo, smooth, uc, u)?;
+                let color = if wi.z() > 0. {
+                    Color::white()
+                } else {
+                    self.base_color
+                };
+                let specular = smooth.then(|| {
+                    (
+                        color * self.weight(lobe) / probability,
+                        chance * probability,
+                    )
+                });
+                (wi, specular)
+            }
+        };
+        if lobe != Lobe::Transmission && wi.z() <= 0. {
+            return None;
+        }
+        Some(LobeSample { wi, lobe, specular })
+    }
+}
+
+/// Local direction sampled from one lobe
+struct LobeSample {
+    wi: Vector3,
+    lobe: Lobe,
+    /// Path weight and probability when the lobe is smooth
+    specular: Option<(Color, f64)>,
+}
+
+fn clearcoat_fresnel(cos_theta: f64) -> f64 {
+    0.04 + 0.96 * schlick_weight(cos_theta)
+}
+
+/// Microfacet reflection without Fresnel
+fn microfacet_reflection(
+    distribution: &TrowbridgeReitz,
+    wo: &Vector3,
+    wi: &Vector3,
+    wm: &Vector3,
+) -> f64 {
+    distribution.d(wm) * distribution.g(wo, wi) / (4. * wo.z() * wi.z())
+}
+
+fn microfacet_reflection_pdf(distribution: &TrowbridgeReitz, wo: &Vector3, wi: &Vector3) -> f64 {
+    let wm = (*wo + *wi).normalize();
+    distribution.visible_d(wo, &wm) / (4. * Vector3::dot(wo, &wm))
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        let frame = hit.shading_frame();
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z() <= 0. {
+            return Color::black();
+        }
+        Surface::new(self, hit, &wo).eval(&wo, &wi)
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        let frame = hit.shading_frame();
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        if wo.z() <= 0. {
+            return 0.;
+        }
+        Surface::new(self, hit, &wo).pdf(&wo, &wi)
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let frame = hit.shading_frame();
+        let wo_local = frame.to_local(wo);
+        if wo_local.z() <= 0. {
+            return None;
+        }
+        let surface = Surface::new(self, hit, &wo_local);
+        let LobeSample {
+            wi: wi_local,
+            lobe,
+            specular,
+        } = surface.sample(&wo_local, uc, u)?;
+
+        let mut flags = if wi_local.z() > 0. {
+            BsdfFlags::REFLECTION
+        } else {
+            BsdfFlags::TRANSMISSION
+        };
+        let wi = frame.local(&wi_local);
+        if let Some((weight, probability)) = specular {
+            return BsdfSample::specular(wi, weight, probability, flags, hit);
+        }
+        flags = flags
+            | if lobe == Lobe::Diffuse {
+                BsdfFlags::DIFFUSE
+            } else {
+                BsdfFlags::GLOSSY
+            };
+        let pdf = surface.pdf(&wo_local, &wi_local);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: surface.eval(&wo_local, &wi_local),
+            pdf,
+            flags,
+        })
+    }
+
+    fn flags(&self, _hit: &RayHit) -> BsdfFlags {
+        if self.transmission > 0. {
+            BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+        } else {
+            BsdfFlags::REFLECTION
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    #[test]
+    fn smooth_glass_scatters_all_light_once() {
+        let glass = Arc::new(
+            Principled::new(Color::white())
+                .with_roughness(0.)
+                .with_transmission(1., 1.5),
+        );
+        for (theta, behind) in [(0.3, false), (1.2, false), (0.5, true)] {
+            let albedo = estimate(glass.clone(), theta, behind).albedo();
+            assert!(near(albedo, Color::white(), 1e-9), "{albedo:?}");
+        }
+    }
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        let materials = [
+            Principled::new(Color::new(0.8, 0.4, 0.2)),
+            Principled::new(Color::new(0.8, 0.4, 0.2))
+                .with_metallic(1.)
+                .with_roughness(0.4),
+            Principled::new(Color::new(0.2, 0.4, 0.8))
+                .with_clearcoat(1., 0.5)
+                .with_sheen(1., 0.5),
+            Principled::new(Color::white())
+                .with_roughness(0.5)
+                .with_transmission(1., 1.5),
+        ];
+        for material in materials {
+            let estimate = estimate(Arc::new(material), 0.6, false);
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+            assert!(estimate.pdf_integral <= 1.02, "{}", estimate.pdf_integral);
+            assert!(estimate.albedo().to_array().iter().all(|&x| x <= 1.01));
+        }
+    }
+}
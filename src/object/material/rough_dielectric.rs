@@ -0,0 +1,254 @@
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    fresnel,
+    microfacet::TrowbridgeReitz,
+    Material, Scatter,
+};
+
+/// GGX reflection and transmission through an interface whose relative
+/// index is `eta` from the side `z` points to, for local directions
+#[derive(Debug, Copy, Clone)]
+pub struct Interface {
+    pub distribution: TrowbridgeReitz,
+    pub eta: f64,
+}
+
+impl Interface {
+    /// Same interface seen with `wo` on the `z` side
+    fn oriented(&self, wo: &Vector3, wi: &Vector3) -> (Self, Vector3, Vector3) {
+        if wo.z() >= 0. {
+            return (*self, *wo, *wi);
+        }
+        let flipped = Self {
+            eta: 1. / self.eta,
+            ..*self
+        };
+        (flipped, -*wo, -*wi)
+    }
+
+    /// Microfacet normal joining `wo` and `wi`, facing `z`
+    fn half_vector(&self, wo: &Vector3, wi: &Vector3) -> Option<Vector3> {
+        let etap = if wi.z() > 0. { 1. } else { self.eta };
+        let wm = *wi * etap + *wo;
+        if wm.is_near_zero() || wo.z() == 0. || wi.z() == 0. {
+            return None;
+        }
+        let wm = wm.normalize();
+        let wm = if wm.z() < 0. { -wm } else { wm };
+        // ? Microfacets seen from behind contribute nothing
+        if Vector3::dot(&wm, wi) * wi.z() < 0. || Vector3::dot(&wm, wo) * wo.z() < 0. {
+            return None;
+        }
+        Some(wm)
+    }
+
+    /// Jacobian of the refracted direction with respect to the microfacet normal
+    fn refraction_jacobian(&self, wo: &Vector3, wi: &Vector3, wm: &Vector3) -> f64 {
+        let denominator = Vector3::dot(wi, wm) + Vector3::dot(wo, wm) / self.eta;
+        Vector3::dot(wi, wm).abs() / (denominator * denominator)
+    }
+
+    /// BSDF value, refracted light is not rescaled by the squared index,
+    /// matching [`super::dielectric::Dielectric`]
+    pub fn eval(&self, wo: &Vector3, wi: &Vector3) -> f64 {
+        let (interface, wo, wi) = self.oriented(wo, wi);
+        let Some(wm) = interface.half_vector(&wo, &wi) else {
+            return 0.;
+        };
+        let distribution = &interface.distribution;
+        let reflectance = fresnel::dielectric(Vector3::dot(&wo, &wm), interface.eta);
+        let dg = distribution.d(&wm) * distribution.g(&wo, &wi);
+        if wi.z() > 0. {
+            return dg * reflectance / (4. * wo.z() * wi.z()).abs();
+        }
+        dg * (1. - reflectance)
+            * interface.refraction_jacobian(&wo, &wi, &wm)
+            * Vector3::dot(&wo, &wm).abs()
+            / (wo.z() * wi.z()).abs()
+    }
+
+    pub fn pdf(&self, wo: &Vector3, wi: &Vector3) -> f64 {
+        let (interface, wo, wi) = self.oriented(wo, wi);
+        let Some(wm) = interface.half_vector(&wo, &wi) else {
+            return 0.;
+        };
+        let reflectance = fresnel::dielectric(Vector3::dot(&wo, &wm), interface.eta);
+        let visible = interface.distribution.visible_d(&wo, &wm);
+        if wi.z() > 0. {
+            return reflectance * visible / (4. * Vector3::dot(&wo, &wm).abs());
+        }
+        (1. - reflectance) * visible * interface.refraction_jacobian(&wo, &wi, &wm)
+    }
+
+    /// Picks reflection or refraction by Fresnel off a sampled visible
+    /// normal, or off the macro normal when `smooth`. Returns `wi` with the
+    /// probability of the chosen event.
+    pub fn sample(
+        &self,
+        wo: &Vector3,
+        smooth: bool,
+        uc: f64,
+        u: (f64, f64),
+    ) -> Option<(Vector3, f64)> {
+        let flipped = wo.z() < 0.;
+        let (interface, wo, _) = self.oriented(wo, wo);
+        let wm = if smooth {
+            Vector3::new(0, 0, 1)
+        } else {
+            interface.distribution.sample_visible(&wo, u)
+        };
+
+        let cos_theta_o = Vector3::dot(&wo, &wm);
+        let reflectance = fresnel::dielectric(cos_theta_o, interface.eta);
+        let (wi, probability) = if uc < reflectance {
+            let wi = (-wo).reflect(&wm);
+            if wi.z() <= 0. {
+                return None;
+            }
+            (wi, reflectance)
+        } else {
+            let eta = interface.eta;
+            let sin2_theta_t = (1. - cos_theta_o * cos_theta_o) / (eta * eta);
+            let cos_theta_t = (1. - sin2_theta_t).max(0.).sqrt();
+            let wi = -wo / eta + (cos_theta_o / eta - cos_theta_t) * wm;
+            if wi.z() >= 0. {
+                return None;
+            }
+            (wi, 1. - reflectance)
+        };
+        Some((if flipped { -wi } else { wi }, probability))
+    }
+}
+
+/// Frosted glass with GGX microfacet reflection and transmission (Walter et al. 2007)
+pub struct RoughDielectric {
+    /// Index of refraction
+    index: f64,
+    distribution: TrowbridgeReitz,
+}
+
+impl RoughDielectric {
+    /// `roughness` is perceptual and given along the tangent and bitangent
+    pub fn new(index: f64, roughness: (f64, f64)) -> Self {
+        Self {
+            index,
+            distribution: TrowbridgeReitz::from_roughness(roughness.0, roughness.1),
+        }
+    }
+
+    pub fn isotropic(index: f64, roughness: f64) -> Self {
+        Self::new(index, (roughness, roughness))
+    }
+
+    /// The local frame always faces the incoming ray, so the relative index
+    /// flips when leaving the medium
+    fn interface(&self, hit: &RayHit) -> Interface {
+        Interface {
+            distribution: self.distribution,
+            eta: if hit.front_face {
+                self.index
+            } else {
+                1. / self.index
+            },
+        }
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        if self.distribution.is_smooth() {
+            return Color::black();
+        }
+        let frame = hit.shading_frame();
+        Color::white()
+            * self
+                .interface(hit)
+                .eval(&frame.to_local(wo), &frame.to_local(wi))
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        if self.distribution.is_smooth() {
+            return 0.;
+        }
+        let frame = hit.shading_frame();
+        self.interface(hit)
+            .pdf(&frame.to_local(wo), &frame.to_local(wi))
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        let frame = hit.shading_frame();
+        let smooth = self.distribution.is_smooth();
+        let wo_local = frame.to_local(wo);
+        let (wi, probability) = self.interface(hit).sample(&wo_local, smooth, uc, u)?;
+        let flags = if wi.z() * wo_local.z() > 0. {
+            BsdfFlags::REFLECTION
+        } else {
+            BsdfFlags::TRANSMISSION
+        };
+        let wi = frame.local(&wi);
+        if smooth {
+            return BsdfSample::specular(wi, Color::white(), probability, flags, hit);
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(hit, wo, &wi),
+            pdf: self.pdf(hit, wo, &wi),
+            flags: flags | BsdfFlags::GLOSSY,
+        })
+    }
+
+    fn flags(&self, _hit: &RayHit) -> BsdfFlags {
+        BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::object::material::{
+        bsdf::testing::{estimate, near},
+        color::Color,
+    };
+
+    #[test]
+    fn sampling_matches_the_integrated_bsdf() {
+        for (theta, behind) in [(0.3, false), (1.2, false), (0.4, true)] {
+            let estimate = estimate(
+                Arc::new(RoughDielectric::isotropic(1.5, 0.5)),
+                theta,
+                behind,
+            );
+            assert!(
+                near(estimate.sampled, estimate.integral, 0.02),
+                "sampled {:?} integral {:?}",
+                estimate.sampled,
+                estimate.integral
+            );
+            assert!(estimate.pdf_integral <= 1.02, "{}", estimate.pdf_integral);
+        }
+    }
+
+    #[test]
+    fn frosted_glass_passes_the_white_furnace() {
+        let albedo = estimate(Arc::new(RoughDielectric::isotropic(1.5, 0.2)), 0.5, false).albedo();
+        assert!(
+            near(albedo, Color::white(), 0.03) && albedo.x() <= 1.01,
+            "{albedo:?}"
+        );
+    }
+}
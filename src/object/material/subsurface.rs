@@ -0,0 +1,153 @@
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{bsdf::BsdfFlags, color::Color, fresnel, Material, Scatter};
+
+/// Random walk subsurface scattering inside closed objects
+///
+/// Rays refract into the object like with a dielectric. Each segment that
+/// ends on the inside of the surface is sampled for a scattering event, in
+/// which case the walk continues in a new direction from within the medium.
+/// Walks are cut off by the integrator's depth, so media with little
+/// absorption need a generous depth. Like [`Dielectric`] the boundary is
+/// smooth, so neither it nor the walk inside have a BSDF to evaluate and
+/// light only reaches the medium through sampled paths.
+///
+/// [`Dielectric`]: super::dielectric::Dielectric
+pub struct Subsurface {
+    /// Index of refraction of the boundary
+    index: f64,
+    /// Extinction coefficient per unit length
+    extinction: Color,
+    /// Probability of scattering rather than absorbing at each event
+    albedo: Color,
+}
+
+impl Subsurface {
+    /// `mean_free_path` is the average distance between events per channel
+    pub fn new(index: f64, mean_free_path: Color, albedo: Color) -> Self {
+        let mut extinction = Color::zero();
+        for i in 0..3 {
+            extinction[i] = 1. / mean_free_path[i].max(1e-6);
+        }
+        Self {
+            index,
+            extinction,
+            albedo,
+        }
+    }
+
+    /// Reflects or refracts through the boundary with exact Fresnel
+    fn cross_boundary(&self, direction: Vector3, hit: &RayHit) -> Vector3 {
+        let eta = if hit.front_face {
+            self.index
+        } else {
+            1. / self.index
+        };
+        let cos_theta = Vector3::dot(&-direction, &hit.normal);
+        if Random::f64() < fresnel::dielectric(cos_theta, eta) {
+            direction.reflect(&hit.normal)
+        } else {
+            direction.refract(&hit.normal, 1. / eta)
+        }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        let direction = ray.direction().normalize();
+        if hit.front_face {
+            return Some(Scatter {
+                attenuation: Color::white(),
+                ray: Ray::of(hit.point, self.cross_boundary(direction, hit)),
+            });
+        }
+
+        // ? Distances are sampled from one channel picked at random and
+        // weighted by the average density over all channels
+        let channel = ((Random::f64() * 3.) as usize).min(2);
+        let distance = -(1. - Random::f64()).ln() / self.extinction[channel];
+        let segment = hit.distance;
+
+        let mut transmittance = Color::zero();
+        let travelled = distance.min(segment);
+        for i in 0..3 {
+            transmittance[i] = (-self.extinction[i] * travelled).exp();
+        }
+
+        if distance < segment {
+            let density = self.extinction * transmittance;
+            let pdf = (density.x() + density.y() + density.z()) / 3.;
+            if pdf <= 0. {
+                return None;
+            }
+            return Some(Scatter {
+                attenuation: self.albedo * density / pdf,
+                ray: Ray::of(*ray.origin() + distance * direction, Vector3::random_unit()),
+            });
+        }
+
+        let pdf = (transmittance.x() + transmittance.y() + transmittance.z()) / 3.;
+        if pdf <= 0. {
+            return None;
+        }
+        Some(Scatter {
+            attenuation: transmittance / pdf,
+            ray: Ray::of(hit.point, self.cross_boundary(direction, hit)),
+        })
+    }
+
+    fn flags(&self, _hit: &RayHit) -> BsdfFlags {
+        BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        object::{geometry::sphere::Sphere, light::Background},
+        render::integrator::{Integrator, PathTracer},
+        vec3,
+        view::ray::HitTarget,
+    };
+
+    /// Mean radiance through a medium ball in a white environment
+    fn furnace(albedo: Color) -> Color {
+        let mut world = HitTarget::new();
+        world.push(Arc::new(Sphere::new(
+            Vector3::zero(),
+            1.,
+            Arc::new(Subsurface::new(1.3, Color::white() * 0.3, albedo)),
+        )));
+        let path = PathTracer::new(1000)
+            .with_min_depth(1000)
+            .with_background(Background::Uniform(Color::white()));
+        let samples = 4000;
+        (0..samples).fold(Color::black(), |sum, _| {
+            let ray = Ray::of(vec3!(0, 0, 4), vec3!(0, 0.1, -1).normalize());
+            sum + path.radiance(&ray, &world)
+        }) / samples as f64
+    }
+
+    #[test]
+    fn scattering_medium_passes_the_white_furnace() {
+        let color = furnace(Color::white());
+        assert!(
+            color.to_array().iter().all(|&x| (x - 1.).abs() < 0.01),
+            "{color:?}"
+        );
+    }
+
+    #[test]
+    fn absorption_darkens_each_channel() {
+        let color = furnace(Color::new(0.99, 0.9, 0.5));
+        assert!(color.x() > color.y() && color.y() > color.z(), "{color:?}");
+        assert!(color.x() < 1. && color.z() > 0., "{color:?}");
+    }
+}
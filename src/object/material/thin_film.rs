@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use crate::{
+    object::geometry::vector::Vector3,
+    util::random::Random,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{
+    bsdf::{BsdfFlags, BsdfSample},
+    color::Color,
+    fresnel::{self, Complex},
+    Material, Scatter,
+};
+
+/// Wavelengths in nanometers standing in for the RGB channels
+const RGB_WAVELENGTHS: [f64; 3] = [610., 550., 465.];
+
+/// Thin transparent film whose reflectance varies with wavelength through
+/// interference, either free standing like a soap bubble or coating a base
+pub struct ThinFilm {
+    /// Film thickness in nanometers
+    thickness: f64,
+    /// Index of refraction of the film
+    index: f64,
+    /// Index of refraction, possibly complex, of what lies under the film
+    substrate: Complex,
+    base: Option<Arc<dyn Material>>,
+}
+
+impl ThinFilm {
+    /// Film with air on both sides, light not reflected passes straight through
+    pub fn bubble(thickness: f64, index: f64) -> Self {
+        Self {
+            thickness,
+            index,
+            substrate: Complex::real(1.),
+            base: None,
+        }
+    }
+
+    /// Film over a substrate of index `substrate`, whose inside is `base`
+    ///
+    /// The film's reflectance already includes the reflection off the
+    /// substrate, so light it does not reflect is absorbed by conducting
+    /// substrates and refracted into dielectric ones. The base only
+    /// scatters light leaving the substrate, it should be a [`Dielectric`]
+    /// of the same index for glass.
+    ///
+    /// [`Dielectric`]: super::dielectric::Dielectric
+    pub fn coating(
+        base: Arc<dyn Material>,
+        thickness: f64,
+        index: f64,
+        substrate: Complex,
+    ) -> Self {
+        Self {
+            thickness,
+            index,
+            substrate,
+            base: Some(base),
+        }
+    }
+
+    /// Per channel reflectance, or the reflectance at `wavelength` on every channel
+    pub fn reflectance(&self, cos_theta: f64, wavelength: Option<f64>) -> Color {
+        let at = |wavelength: f64| {
+            fresnel::thin_film(
+                cos_theta,
+                self.index,
+                self.thickness,
+                self.substrate,
+                wavelength,
+            )
+        };
+        match wavelength {
+            Some(wavelength) => Color::white() * at(wavelength),
+            None => {
+                let [r, g, b] = RGB_WAVELENGTHS.map(at);
+                Color::new(r, g, b)
+            }
+        }
+    }
+}
+
+impl ThinFilm {
+    /// Material handling `hit` instead of the film, the base for light
+    /// leaving the substrate
+    fn inside(&self, hit: &RayHit) -> Option<&Arc<dyn Material>> {
+        self.base.as_ref().filter(|_| !hit.front_face)
+    }
+}
+
+impl Material for ThinFilm {
+    fn scatter(&self, ray: &Ray, hit: &RayHit) -> Option<Scatter> {
+        if let Some(base) = self.inside(hit) {
+            return base.scatter(ray, hit);
+        }
+        let wo = -ray.direction().normalize();
+        let sample = self.sample(hit, &wo, Random::f64(), (Random::f64(), Random::f64()))?;
+        Some(sample.into_scatter(hit))
+    }
+
+    /// The film itself is smooth and can only be sampled
+    fn eval(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> Color {
+        match self.inside(hit) {
+            Some(base) => base.eval(hit, wo, wi),
+            None => Color::black(),
+        }
+    }
+
+    fn pdf(&self, hit: &RayHit, wo: &Vector3, wi: &Vector3) -> f64 {
+        match self.inside(hit) {
+            Some(base) => base.pdf(hit, wo, wi),
+            None => 0.,
+        }
+    }
+
+    fn sample(&self, hit: &RayHit, wo: &Vector3, uc: f64, u: (f64, f64)) -> Option<BsdfSample> {
+        if let Some(base) = self.inside(hit) {
+            return base.sample(hit, wo, uc, u);
+        }
+
+        let cos_theta = Vector3::dot(wo, &hit.normal);
+        let reflectance = self.reflectance(cos_theta, hit.wavelength);
+        let reflected = (-*wo).reflect(&hit.normal);
+        if self.substrate.im > 0. {
+            return BsdfSample::specular(reflected, reflectance, 1., BsdfFlags::REFLECTION, hit);
+        }
+
+        // ? Reflect with the average reflectance and reweight each channel
+        let probability =
+            ((reflectance.x() + reflectance.y() + reflectance.z()) / 3.).clamp(1e-3, 1. - 1e-3);
+        if uc < probability {
+            return BsdfSample::specular(
+                reflected,
+                reflectance / probability,
+                probability,
+                BsdfFlags::REFLECTION,
+                hit,
+            );
+        }
+
+        // ? The film is too thin to offset the ray, which bends as if
+        // entering the substrate directly
+        BsdfSample::specular(
+            (-*wo).refract(&hit.normal, 1. / self.substrate.re),
+            (Color::white() - reflectance) / (1. - probability),
+            1. - probability,
+            BsdfFlags::TRANSMISSION,
+            hit,
+        )
+    }
+
+    fn flags(&self, hit: &RayHit) -> BsdfFlags {
+        match self.inside(hit) {
+            Some(base) => base.flags(hit),
+            None if self.substrate.im > 0. => BsdfFlags::REFLECTION,
+            None => BsdfFlags::REFLECTION | BsdfFlags::TRANSMISSION,
+        }
+    }
+
+    fn emitted(&self, hit: &RayHit) -> Color {
+        match &self.base {
+            Some(base) => base.emitted(hit),
+            None => Color::black(),
+        }
+    }
+
+    /// Interference makes the reflectance depend on the exact wavelength
+    fn dispersive(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::material::bsdf::testing::{estimate, near};
+
+    #[test]
+    fn quarter_wave_coating_cancels_reflection() {
+        // ? A film of index √n a quarter wavelength thick reflects nothing
+        // off glass at that wavelength, yet plain glass reflects 4%
+        let index = 1.5_f64.sqrt();
+        let coating = |thickness: f64| ThinFilm {
+            thickness,
+            index,
+            substrate: Complex::real(1.5),
+            base: None,
+        };
+        let coated = coating(550. / (4. * index)).reflectance(1., Some(550.));
+        let bare = coating(0.).reflectance(1., Some(550.));
+        assert!(coated.x() < 1e-6, "{coated:?}");
+        assert!((bare.x() - 0.04).abs() < 1e-6, "{bare:?}");
+    }
+
+    #[test]
+    fn bubble_colors_vary_with_thickness() {
+        let thin = ThinFilm::bubble(300., 1.33).reflectance(1., None);
+        let thick = ThinFilm::bubble(400., 1.33).reflectance(1., None);
+        assert!(!near(thin, thick, 0.01), "{thin:?} {thick:?}");
+        assert!(!near(thin, Color::white() * thin.x(), 0.01), "{thin:?}");
+    }
+
+    #[test]
+    fn bubble_passes_the_white_furnace() {
+        for theta in [0.1, 1.2] {
+            let albedo = estimate(Arc::new(ThinFilm::bubble(350., 1.33)), theta, false).albedo();
+            assert!(near(albedo, Color::white(), 0.01), "{albedo:?}");
+        }
+    }
+}
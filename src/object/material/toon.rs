@@ -0,0 +1,82 @@
+use crate::{
+    object::geometry::vector::Vector3,
+    view::ray::{Ray, RayHit},
+};
+
+use super::{color::Color, Material, Scatter};
+
+/// Cel shading with the diffuse term of each light quantized into flat bands,
+/// the surface does not scatter and is only lit by light sampling, so
+/// shadows and light colors come from the scene's lights. Its ambient color
+/// is added by the [`GBuffer`](crate::render::outline::GBuffer) pass.
+pub struct Toon {
+    color: Color,
+    bands: u32,
+    /// Fraction of the color shown where no light reaches
+    ambient: f64,
+}
+
+impl Toon {
+    pub fn new(color: Color, bands: u32) -> Self {
+        Self {
+            color,
+            bands: bands.max(1),
+            ambient: 0.1,
+        }
+    }
+
+    pub fn with_ambient(mut self, ambient: f64) -> Self {
+        self.ambient = ambient;
+        self
+    }
+}
+
+impl Material for Toon {
+    fn scatter(&self, _: &Ray, _: &RayHit) -> Option<Scatter> {
+        None
+    }
+
+    /// Quantized cosine over the cosine, so light samples land on a band
+    fn eval(&self, hit: &RayHit, _wo: &Vector3, wi: &Vector3) -> Color {
+        let diffuse = Vector3::dot(&hit.normal, wi);
+        if diffuse <= 0. {
+            return Color::black();
+        }
+        let bands = self.bands as f64;
+        // ? Any light at all lands in the first band
+        let band = (diffuse * bands).ceil() / bands;
+        self.color * (band / diffuse)
+    }
+
+    fn ambient(&self, _hit: &RayHit) -> Color {
+        self.ambient * self.color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::vec3;
+
+    #[test]
+    fn ambient_lights_nothing_and_bands_are_flat() {
+        let toon = Arc::new(Toon::new(Color::new(1, 0.5, 0.25), 2).with_ambient(0.2));
+        let ray = Ray::of(vec3!(0, 1, 0), vec3!(0, -1, 0));
+        let hit = RayHit::new(&ray, 1., Vector3::up(), toon.clone());
+
+        assert!(toon.emitted(&hit).is_near_zero());
+        assert_eq!(toon.ambient(&hit).to_array(), [0.2, 0.1, 0.05]);
+        // ? Every light direction within a band reflects the same color
+        let shade = |wi: Vector3| {
+            let wi = wi.normalize();
+            toon.eval(&hit, &Vector3::up(), &wi) * Vector3::dot(&wi, &Vector3::up())
+        };
+        assert_eq!(
+            shade(vec3!(1, 0.2, 0)).to_array(),
+            shade(vec3!(1, 0.5, 0)).to_array()
+        );
+        assert_eq!(shade(vec3!(0.1, 1, 0)).to_array(), [1., 0.5, 0.25]);
+    }
+}
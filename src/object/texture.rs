@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageResult};
+
+use super::{geometry::vector::Vector3, material::color::Color};
+
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, point: &Vector3) -> Color;
+
+    /// Channel average, for textures used as scalar fields
+    fn scalar(&self, u: f64, v: f64, point: &Vector3) -> f64 {
+        let color = self.value(u, v, point);
+        (color.x() + color.y() + color.z()) / 3.
+    }
+}
+
+/// Decodes an sRGB encoded value in `[0, 1]` to linear
+pub fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _: f64, _: f64, _: &Vector3) -> Color {
+        self.color
+    }
+}
+
+/// Bilinearly filtered image, repeated outside of the unit square
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    texels: Vec<Color>,
+}
+
+impl ImageTexture {
+    /// Texels in row-major order starting from the top left corner
+    pub fn new(width: usize, height: usize, texels: Vec<Color>) -> Self {
+        assert_eq!(texels.len(), width * height, "texel count must match size");
+        Self {
+            width,
+            height,
+            texels,
+        }
+    }
+
+    /// Loads a color image, see [`ImageTexture::from_image`]
+    pub fn load(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::from_image(image::open(path)?))
+    }
+
+    /// Loads an image holding data such as heights or normals, whose
+    /// values are used as they are stored
+    pub fn load_linear(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::from_linear_image(image::open(path)?))
+    }
+
+    /// Color image whose integer formats are sRGB encoded and linearized,
+    /// floating point images are already linear
+    pub fn from_image(image: DynamicImage) -> Self {
+        let srgb = !matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        Self::decode(image, srgb)
+    }
+
+    pub fn from_linear_image(image: DynamicImage) -> Self {
+        Self::decode(image, false)
+    }
+
+    fn decode(image: DynamicImage, srgb: bool) -> Self {
+        let image = image.into_rgb32f();
+        let decode = |value: f32| {
+            let value = value as f64;
+            if srgb {
+                srgb_to_linear(value)
+            } else {
+                value
+            }
+        };
+        let texels = image
+            .pixels()
+            .map(|pixel| Color::new(decode(pixel[0]), decode(pixel[1]), decode(pixel[2])))
+            .collect();
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            texels,
+        }
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.texels[(y % self.height) * self.width + (x % self.width)]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _: &Vector3) -> Color {
+        if self.texels.is_empty() {
+            return Color::black();
+        }
+
+        // ? Image rows go top to bottom while v goes bottom to top
+        let x = u.rem_euclid(1.) * self.width as f64 - 0.5;
+        let y = (1. - v.rem_euclid(1.)) * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (dx, dy) = (x - x0, y - y0);
+        let x0 = (x0 as isize).rem_euclid(self.width as isize) as usize;
+        let y0 = (y0 as isize).rem_euclid(self.height as isize) as usize;
+
+        let top = Vector3::lerp(&self.texel(x0, y0), &self.texel(x0 + 1, y0), dx);
+        let bottom = Vector3::lerp(&self.texel(x0, y0 + 1), &self.texel(x0 + 1, y0 + 1), dx);
+        Vector3::lerp(&top, &bottom, dy)
+    }
+}
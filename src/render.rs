@@ -1 +1,5 @@
+pub mod film;
+pub mod integrator;
+pub mod outline;
 pub mod pixel;
+pub mod spectral;
@@ -0,0 +1,53 @@
+use std::sync::Mutex;
+
+use crate::object::material::color::Color;
+
+/// `(u, v)` for [`Camera::get_ray`](crate::view::camera::Camera::get_ray) at
+/// `offset` within pixel `(i, j)`, rows from the top, of a `width` by
+/// `height` image
+pub fn pixel_uv(i: usize, j: usize, offset: (f64, f64), width: usize, height: usize) -> (f64, f64) {
+    (
+        (i as f64 + offset.0) / width as f64,
+        ((height - 1 - j) as f64 + offset.1) / height as f64,
+    )
+}
+
+/// Image that light paths add to at whatever pixel they reach the camera
+pub struct SplatFilm {
+    width: usize,
+    height: usize,
+    pixels: Vec<Mutex<Color>>,
+}
+
+impl SplatFilm {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: (0..width * height)
+                .map(|_| Mutex::new(Color::black()))
+                .collect(),
+        }
+    }
+
+    /// Adds `color` to the pixel whose [`pixel_uv`] covers `uv`
+    pub fn splat(&self, uv: (f64, f64), color: Color) {
+        let i = ((uv.0 * self.width as f64) as usize).min(self.width - 1);
+        let row = ((uv.1 * self.height as f64) as usize).min(self.height - 1);
+        let j = self.height - 1 - row;
+        *self.pixels[j * self.width + i].lock().unwrap() += color;
+    }
+
+    /// Accumulated light, rows from the top, scaled to add to an image
+    /// averaging `samples` per pixel where each sample traced one light path
+    pub fn image(&self, samples: u32) -> Vec<Color> {
+        // ? Splats estimate a density over the image, a pixel covers
+        // 1 / (width height) of it
+        let pixel_area = (self.width * self.height) as f64;
+        let paths = (self.width * self.height) as f64 * samples as f64;
+        self.pixels
+            .iter()
+            .map(|pixel| *pixel.lock().unwrap() * (pixel_area / paths))
+            .collect()
+    }
+}
@@ -0,0 +1,641 @@
+pub mod bdpt;
+
+use std::sync::Arc;
+
+use crate::{
+    object::{
+        geometry::{onb::Onb, vector::Vector3},
+        light::{sky, Background, Light},
+        material::{bsdf::sample_cosine_hemisphere, color::Color},
+    },
+    render::spectral::{SampledSpectrum, SampledWavelengths},
+    util::random::Random,
+    view::{
+        camera::Camera,
+        ray::{Hit, HitTarget, Ray, RayHit},
+    },
+};
+
+use self::bdpt::Bdpt;
+
+/// Algorithm estimating the radiance arriving along camera rays
+pub trait Integrator: Send + Sync {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color;
+
+    /// Light that reached other pixels than the one being sampled, rows from
+    /// the top, to add to the image once it averages `samples` per pixel
+    fn splats(&self, _samples: u32) -> Option<Vec<Color>> {
+        None
+    }
+}
+
+/// Names accepted by [`from_name`]
+pub const NAMES: [&str; 9] = [
+    "normals",
+    "ao",
+    "whitted",
+    "path",
+    "hemisphere",
+    "spectral",
+    "direct",
+    "nee",
+    "bdpt",
+];
+
+/// Bounces a [`PathTracer`] traces before Russian roulette unless told otherwise
+pub const DEFAULT_MIN_DEPTH: u32 = 3;
+
+/// Scene inputs the integrators of [`from_name`] are built from
+pub struct Settings {
+    pub lights: Vec<Arc<dyn Light>>,
+    pub max_depth: u32,
+    /// Bounces always traced before Russian roulette may end a path
+    pub min_depth: u32,
+    /// Camera the image is rendered from, needed to splat light paths
+    pub camera: Option<Camera>,
+    /// Width and height of the rendered image
+    pub resolution: (usize, usize),
+    /// Radiance of rays leaving the scene
+    pub background: Background,
+}
+
+impl Settings {
+    pub fn new(lights: Vec<Arc<dyn Light>>, max_depth: u32) -> Self {
+        Self {
+            lights,
+            max_depth,
+            min_depth: DEFAULT_MIN_DEPTH,
+            camera: None,
+            resolution: (0, 0),
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_min_depth(mut self, min_depth: u32) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    pub fn with_camera(mut self, camera: Camera, width: usize, height: usize) -> Self {
+        self.camera = Some(camera);
+        self.resolution = (width, height);
+        self
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+/// Builds the integrator called `name`, see [`NAMES`], `"bdpt"` needs
+/// [`Settings::with_camera`]
+pub fn from_name(name: &str, settings: Settings) -> Option<Box<dyn Integrator>> {
+    let Settings {
+        lights,
+        max_depth,
+        min_depth,
+        camera,
+        resolution: (width, height),
+        background,
+    } = settings;
+    let integrator: Box<dyn Integrator> = match name {
+        "normals" => Box::new(Normals),
+        "ao" => Box::new(AmbientOcclusion::new(16, f64::INFINITY)),
+        "whitted" => Box::new(Whitted::new(lights, max_depth).with_background(background)),
+        "path" => Box::new(
+            PathTracer::new(max_depth)
+                .with_min_depth(min_depth)
+                .with_background(background),
+        ),
+        "hemisphere" => Box::new(HemispherePathTracer::new(max_depth).with_background(background)),
+        "spectral" => Box::new(SpectralPathTracer::new(max_depth).with_background(background)),
+        "direct" => Box::new(DirectLighting::new(lights, max_depth).with_background(background)),
+        "nee" => Box::new(NextEventEstimation::new(lights, max_depth).with_background(background)),
+        "bdpt" => Box::new(
+            Bdpt::new(camera?, lights, max_depth, width, height).with_background(background),
+        ),
+        _ => return None,
+    };
+    Some(integrator)
+}
+
+/// Power heuristic weight of a strategy with density `pdf` against `other`
+pub fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (pdf2, other2) = (pdf * pdf, other * other);
+    if pdf2 + other2 == 0. {
+        return 0.;
+    }
+    pdf2 / (pdf2 + other2)
+}
+
+/// Light reflected towards `wo` from one sample of each light, optionally
+/// weighted against material sampling with the power heuristic
+fn direct_lighting(
+    hit: &RayHit,
+    wo: &Vector3,
+    world: &HitTarget,
+    lights: &[Arc<dyn Light>],
+    mis: bool,
+) -> Color {
+    let mut color = Color::black();
+    for light in lights.iter() {
+        let Some(sample) = light.sample_li(&hit.point, (Random::f64(), Random::f64())) else {
+            continue;
+        };
+        let cos_theta = Vector3::dot(&sample.direction, &hit.normal);
+        let transmitted = cos_theta < 0.;
+        if cos_theta == 0.
+            || sample.pdf <= 0.
+            || (transmitted && !hit.material.flags(hit).is_transmission())
+        {
+            continue;
+        }
+        let f = hit.material.eval(hit, wo, &sample.direction);
+        if f.is_near_zero() || !sample.is_visible(world, &hit.point) {
+            continue;
+        }
+        // ? Lights that material sampling cannot find, like point lights or
+        // the sun, keep their full weight
+        let reachable = light.pdf_li(&hit.point, &sample.direction, sample.distance) > 0.;
+        let weight = if mis && !sample.delta && reachable {
+            power_heuristic(sample.pdf, hit.material.pdf(hit, wo, &sample.direction))
+        } else {
+            1.
+        };
+        color += f * sample.radiance * (cos_theta.abs() * weight / sample.pdf);
+    }
+    color
+}
+
+/// Shading normals mapped to colors
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        if let Some(hit) = world.hit(ray, (0., f64::INFINITY)) {
+            return 0.5 * (hit.normal + Color::ones());
+        }
+        sky(ray.direction())
+    }
+}
+
+/// Fraction of the cosine weighted hemisphere left unoccluded within `distance`
+pub struct AmbientOcclusion {
+    samples: u32,
+    distance: f64,
+}
+
+impl AmbientOcclusion {
+    pub fn new(samples: u32, distance: f64) -> Self {
+        Self { samples, distance }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        let Some(hit) = world.hit(ray, (0.001, f64::INFINITY)) else {
+            return Color::white();
+        };
+        let basis = Onb::from_normal(&hit.normal);
+        let unoccluded = (0..self.samples)
+            .filter(|_| {
+                let direction =
+                    basis.local(&sample_cosine_hemisphere((Random::f64(), Random::f64())));
+                world
+                    .hit(&Ray::of(hit.point, direction), (0.001, self.distance))
+                    .is_none()
+            })
+            .count();
+        Color::white() * (unoccluded as f64 / self.samples.max(1) as f64)
+    }
+}
+
+/// Direct lighting from explicit lights, following only specular bounces
+pub struct Whitted {
+    lights: Vec<Arc<dyn Light>>,
+    max_depth: u32,
+    background: Background,
+}
+
+impl Whitted {
+    pub fn new(lights: Vec<Arc<dyn Light>>, max_depth: u32) -> Self {
+        Self {
+            lights,
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    fn trace(&self, ray: &Ray, world: &HitTarget, depth: u32) -> Color {
+        if depth == 0 {
+            return Color::black();
+        }
+
+        let Some(hit) = world.hit(ray, (0.001, f64::INFINITY)) else {
+            return self.background.radiance(ray.direction());
+        };
+        let wo = -ray.direction().normalize();
+        let mut color =
+            hit.material.emitted(&hit) + direct_lighting(&hit, &wo, world, &self.lights, false);
+        let sample = hit
+            .material
+            .sample(&hit, &wo, Random::f64(), (Random::f64(), Random::f64()));
+        if let Some(sample) = sample.filter(|sample| sample.flags.is_specular()) {
+            let scatter = sample.into_scatter(&hit);
+            color += scatter.attenuation * self.trace(&scatter.ray, world, depth - 1);
+        }
+        color
+    }
+}
+
+impl Integrator for Whitted {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        self.trace(ray, world, self.max_depth)
+    }
+}
+
+/// Unidirectional path tracer relying on material sampling alone
+pub struct PathTracer {
+    /// Bounces always traced before Russian roulette may end a path
+    min_depth: u32,
+    max_depth: u32,
+    background: Background,
+}
+
+impl PathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            min_depth: DEFAULT_MIN_DEPTH,
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_min_depth(mut self, min_depth: u32) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        let mut color = Color::black();
+        let mut throughput = Color::white();
+        let mut ray = Ray::of(*ray.origin(), *ray.direction());
+
+        for depth in 0..self.max_depth {
+            let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) else {
+                color += throughput * self.background.radiance(ray.direction());
+                break;
+            };
+            color += throughput * hit.material.emitted(&hit);
+            let Some(scatter) = hit.material.scatter(&ray, &hit) else {
+                break;
+            };
+            throughput = throughput * scatter.attenuation;
+            ray = scatter.ray;
+
+            // ? Paths carrying little light are ended early, the survivors
+            // are boosted by the same odds so the estimate stays unbiased
+            if depth + 1 >= self.min_depth {
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(1.);
+                if survival <= 0. || Random::f64() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+        color
+    }
+}
+
+/// [`PathTracer`] whose first bounce is uniform over the hemisphere and
+/// keeps half the light
+pub struct HemispherePathTracer {
+    max_depth: u32,
+    background: Background,
+}
+
+impl HemispherePathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl Integrator for HemispherePathTracer {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        if self.max_depth == 0 {
+            return Color::black();
+        }
+
+        if let Some(hit) = world.hit(ray, (0.001, f64::INFINITY)) {
+            let diffuse_target = hit.point + hit.normal + Vector3::random_in_hemisphere(hit.normal);
+            let bounce = Ray::of(hit.point, diffuse_target - hit.point);
+            return 0.5
+                * PathTracer::new(self.max_depth - 1)
+                    .with_min_depth(self.max_depth - 1)
+                    .with_background(self.background)
+                    .radiance(&bounce, world);
+        }
+        self.background.radiance(ray.direction())
+    }
+}
+
+/// [`PathTracer`] carrying sampled wavelengths, following the hero
+/// wavelength alone once a dispersive material splits them
+pub struct SpectralPathTracer {
+    max_depth: u32,
+    background: Background,
+}
+
+impl SpectralPathTracer {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Radiance at `wavelengths` arriving along `ray`
+    pub fn spectral_radiance(
+        &self,
+        ray: &Ray,
+        world: &HitTarget,
+        wavelengths: &mut SampledWavelengths,
+    ) -> SampledSpectrum {
+        let mut radiance = SampledSpectrum::zero();
+        let mut throughput = SampledSpectrum::constant(1.);
+        let mut ray = Ray::of(*ray.origin(), *ray.direction()).with_wavelength(wavelengths.hero());
+
+        for _ in 0..self.max_depth {
+            let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) else {
+                radiance += throughput
+                    * SampledSpectrum::from_rgb(
+                        self.background.radiance(ray.direction()),
+                        wavelengths,
+                    );
+                break;
+            };
+            radiance +=
+                throughput * SampledSpectrum::from_rgb(hit.material.emitted(&hit), wavelengths);
+            let Some(scatter) = hit.material.scatter(&ray, &hit) else {
+                break;
+            };
+            if hit.material.dispersive() {
+                wavelengths.terminate_secondary();
+            }
+            throughput = throughput * SampledSpectrum::from_rgb(scatter.attenuation, wavelengths);
+            ray = scatter.ray.with_wavelength(wavelengths.hero());
+        }
+        radiance
+    }
+}
+
+impl Integrator for SpectralPathTracer {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        let mut wavelengths = SampledWavelengths::sample_visible(Random::f64());
+        let radiance = self.spectral_radiance(ray, world, &mut wavelengths);
+        wavelengths.to_rgb(&radiance).clamp_each(0, f64::MAX)
+    }
+}
+
+/// [`PathTracer`] adding one shadow tested sample of every light at each hit
+pub struct DirectLighting {
+    lights: Vec<Arc<dyn Light>>,
+    max_depth: u32,
+    background: Background,
+}
+
+impl DirectLighting {
+    pub fn new(lights: Vec<Arc<dyn Light>>, max_depth: u32) -> Self {
+        Self {
+            lights,
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// `scatter_pdf` is the density of the material sample that produced
+    /// `ray`, zero for camera rays and specular bounces
+    fn trace(&self, ray: &Ray, world: &HitTarget, depth: u32, scatter_pdf: f64) -> Color {
+        if depth == 0 {
+            return Color::black();
+        }
+
+        // ? Emitters among the lights were already counted by the light
+        // samples of the previous hit, unless the bounce could not be
+        let sampled = |distance: f64| {
+            let direction = ray.direction().normalize();
+            scatter_pdf > 0.
+                && self
+                    .lights
+                    .iter()
+                    .any(|light| light.pdf_li(ray.origin(), &direction, distance) > 0.)
+        };
+
+        let Some(hit) = world.hit(ray, (0.001, f64::INFINITY)) else {
+            if sampled(f64::INFINITY) {
+                return Color::black();
+            }
+            return self.background.radiance(ray.direction());
+        };
+        let wo = -ray.direction().normalize();
+        let mut color = direct_lighting(&hit, &wo, world, &self.lights, false);
+        if !sampled(hit.distance) {
+            color += hit.material.emitted(&hit);
+        }
+        if let Some(scatter) = hit.material.scatter(ray, &hit) {
+            let pdf = hit
+                .material
+                .pdf(&hit, &wo, &scatter.ray.direction().normalize());
+            color += scatter.attenuation * self.trace(&scatter.ray, world, depth - 1, pdf);
+        }
+        color
+    }
+}
+
+impl Integrator for DirectLighting {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        self.trace(ray, world, self.max_depth, 0.)
+    }
+}
+
+/// Path tracer with next event estimation: every hit samples each light and
+/// the material, weighting both with multiple importance sampling
+pub struct NextEventEstimation {
+    lights: Vec<Arc<dyn Light>>,
+    max_depth: u32,
+    background: Background,
+}
+
+impl NextEventEstimation {
+    pub fn new(lights: Vec<Arc<dyn Light>>, max_depth: u32) -> Self {
+        Self {
+            lights,
+            max_depth,
+            background: Background::Sky,
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+}
+
+impl Integrator for NextEventEstimation {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        let mut color = Color::black();
+        let mut throughput = Color::white();
+        let mut ray = Ray::of(*ray.origin(), *ray.direction());
+        // ? Density of the material sample that produced `ray`, camera rays and
+        // specular bounces have none and see emitters at full weight
+        let mut scatter_pdf = 0.;
+
+        for _ in 0..self.max_depth {
+            let wo = -ray.direction().normalize();
+            let emitter_weight = |distance: f64| {
+                if scatter_pdf <= 0. {
+                    return 1.;
+                }
+                let light_pdf: f64 = self
+                    .lights
+                    .iter()
+                    .map(|light| light.pdf_li(ray.origin(), &-wo, distance))
+                    .sum();
+                power_heuristic(scatter_pdf, light_pdf)
+            };
+
+            let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) else {
+                color += throughput
+                    * self.background.radiance(ray.direction())
+                    * emitter_weight(f64::INFINITY);
+                break;
+            };
+
+            let emitted = hit.material.emitted(&hit);
+            if !emitted.is_near_zero() {
+                color += throughput * emitted * emitter_weight(hit.distance);
+            }
+
+            color += throughput * direct_lighting(&hit, &wo, world, &self.lights, true);
+
+            let Some(scatter) = hit.material.scatter(&ray, &hit) else {
+                break;
+            };
+            scatter_pdf = hit
+                .material
+                .pdf(&hit, &wo, &scatter.ray.direction().normalize());
+            throughput = throughput * scatter.attenuation;
+            ray = scatter.ray;
+        }
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{geometry::sphere::Sphere, light::SphereLight, material::lambertian::Lambertian},
+        vec3,
+    };
+
+    #[test]
+    fn black_background_turns_off_the_sky() {
+        let ray = Ray::of(Vector3::zero(), vec3!(0, 1, 0));
+        let camera = Camera::new(
+            Vector3::zero(),
+            vec3!(0, 0, -1),
+            Vector3::up(),
+            40.,
+            1.,
+            0.,
+            1.,
+        );
+        for name in NAMES
+            .iter()
+            .filter(|&&name| !matches!(name, "normals" | "ao"))
+        {
+            let settings = Settings::new(vec![], 4)
+                .with_camera(camera.clone(), 1, 1)
+                .with_background(Background::Uniform(Color::black()));
+            let integrator = from_name(name, settings).unwrap();
+            let color = integrator.radiance(&ray, &HitTarget::new());
+            assert!(color.is_near_zero(), "{name} saw {color:?}");
+        }
+    }
+
+    #[test]
+    fn power_heuristic_favors_the_denser_strategy() {
+        assert_eq!(power_heuristic(1., 1.), 0.5);
+        assert!((power_heuristic(2., 1.) - 0.8).abs() < 1e-12);
+        assert!((power_heuristic(2., 1.) + power_heuristic(1., 2.) - 1.).abs() < 1e-12);
+        assert_eq!(power_heuristic(1., 0.), 1.);
+        assert_eq!(power_heuristic(0., 0.), 0.);
+    }
+
+    #[test]
+    fn light_sampling_agrees_with_path_tracing() {
+        // ? A large light keeps the path traced reference from being too noisy
+        let light = SphereLight::new(vec3!(0, 5, 0), 3., Color::white());
+        let mut world = HitTarget::new();
+        world.push(Arc::new(light.sphere()));
+        world.push(Arc::new(Sphere::new(
+            Vector3::zero(),
+            1.,
+            Arc::new(Lambertian::new(Color::new(0.8, 0.5, 0.2))),
+        )));
+        let lights: Vec<Arc<dyn Light>> = vec![Arc::new(light)];
+        let black = Background::Uniform(Color::black());
+
+        let ray = || Ray::of(vec3!(0, 0, 4), vec3!(0, 0.8, -3.4).normalize());
+        let samples = 40000;
+        let mean = |integrator: &dyn Integrator| {
+            (0..samples).fold(Color::black(), |sum, _| {
+                sum + integrator.radiance(&ray(), &world)
+            }) / samples as f64
+        };
+        let path = mean(&PathTracer::new(4).with_min_depth(4).with_background(black));
+        let direct = mean(&DirectLighting::new(lights.clone(), 4).with_background(black));
+        let nee = mean(&NextEventEstimation::new(lights, 4).with_background(black));
+        for (name, color) in [("direct", direct), ("nee", nee)] {
+            for (c, p) in color.to_array().into_iter().zip(path.to_array()) {
+                assert!(p > 0. && (c - p).abs() < 0.03 * p, "{name} {c} path {p}");
+            }
+        }
+    }
+}
@@ -0,0 +1,776 @@
+use std::sync::Arc;
+
+use crate::{
+    object::{
+        geometry::vector::Vector3,
+        light::{Background, Light},
+        material::color::Color,
+    },
+    render::film::SplatFilm,
+    util::random::Random,
+    view::{
+        camera::Camera,
+        ray::{Hit, HitTarget, Ray, RayHit},
+    },
+};
+
+use super::Integrator;
+
+#[derive(Clone)]
+enum VertexKind {
+    Camera,
+    /// Point on the light of that index, `delta` for point lights
+    Light {
+        index: usize,
+        delta: bool,
+    },
+    /// Background seen by a camera subpath leaving the scene
+    Infinite,
+    Surface(Box<RayHit>),
+}
+
+#[derive(Clone)]
+struct Vertex {
+    kind: VertexKind,
+    point: Vector3,
+    /// Surface normal, zero for points not on a surface
+    normal: Vector3,
+    /// Unit direction towards the previous vertex of the subpath
+    wo: Vector3,
+    /// Throughput of the subpath up to and including this vertex
+    beta: Color,
+    /// Area density of this vertex when sampled from the previous one
+    pdf_fwd: f64,
+    /// Area density of this vertex when sampled from the next one
+    pdf_rev: f64,
+    /// Whether the vertex scattered through a specular lobe
+    delta: bool,
+}
+
+impl Vertex {
+    fn endpoint(kind: VertexKind, point: Vector3, beta: Color) -> Self {
+        Self {
+            kind,
+            point,
+            normal: Vector3::zero(),
+            wo: Vector3::zero(),
+            beta,
+            pdf_fwd: 0.,
+            pdf_rev: 0.,
+            delta: false,
+        }
+    }
+
+    fn is_on_surface(&self) -> bool {
+        !self.normal.is_near_zero()
+    }
+
+    fn direction_to(&self, other: &Vertex) -> Vector3 {
+        (other.point - self.point).normalize()
+    }
+
+    /// BSDF for light arriving from `next` and leaving towards the previous
+    /// vertex, black for anything but surfaces
+    fn f(&self, next: &Vertex) -> Color {
+        match &self.kind {
+            VertexKind::Surface(hit) => hit.material.eval(hit, &self.wo, &self.direction_to(next)),
+            _ => Color::black(),
+        }
+    }
+}
+
+/// Densities of a vertex that the MIS weight of a strategy may override
+#[derive(Copy, Clone)]
+struct Density {
+    fwd: f64,
+    rev: f64,
+    delta: bool,
+}
+
+impl From<&Vertex> for Density {
+    fn from(vertex: &Vertex) -> Self {
+        Self {
+            fwd: vertex.pdf_fwd,
+            rev: vertex.pdf_rev,
+            delta: vertex.delta,
+        }
+    }
+}
+
+/// Bidirectional path tracer: a camera and a light subpath are traced for
+/// every sample and all their connections are weighted with multiple
+/// importance sampling. Connections straight to the camera land on other
+/// pixels and are collected in [`Integrator::splats`].
+pub struct Bdpt {
+    camera: Camera,
+    lights: Vec<Arc<dyn Light>>,
+    max_depth: u32,
+    background: Background,
+    film: SplatFilm,
+}
+
+impl Bdpt {
+    /// `width` and `height` are those of the rendered image
+    pub fn new(
+        camera: Camera,
+        lights: Vec<Arc<dyn Light>>,
+        max_depth: u32,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        Self {
+            camera,
+            lights,
+            max_depth,
+            background: Background::Sky,
+            film: SplatFilm::new(width, height),
+        }
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    fn light_choice_pdf(&self) -> f64 {
+        if self.lights.is_empty() {
+            return 0.;
+        }
+        1. / self.lights.len() as f64
+    }
+
+    fn is_infinite_light(&self, vertex: &Vertex) -> bool {
+        match vertex.kind {
+            VertexKind::Infinite => true,
+            VertexKind::Light { index, .. } => self.lights[index].is_infinite(),
+            _ => false,
+        }
+    }
+
+    fn is_light(&self, vertex: &Vertex) -> bool {
+        match &vertex.kind {
+            VertexKind::Light { .. } | VertexKind::Infinite => true,
+            VertexKind::Surface(hit) => !hit.material.emitted(hit).is_near_zero(),
+            VertexKind::Camera => false,
+        }
+    }
+
+    /// Radiance emitted by a light vertex towards `to`
+    fn le(&self, vertex: &Vertex, to: &Vertex) -> Color {
+        match &vertex.kind {
+            VertexKind::Infinite => self.background.radiance(&-vertex.direction_to(to)),
+            VertexKind::Surface(hit) => hit.material.emitted(hit),
+            _ => Color::black(),
+        }
+    }
+
+    /// Light whose surface `vertex` lies on, as seen from `from`
+    fn emitter_light(&self, vertex: &Vertex, from: &Vertex) -> Option<usize> {
+        if let VertexKind::Light { index, .. } = vertex.kind {
+            return Some(index);
+        }
+        let offset = vertex.point - from.point;
+        let distance = offset.magnitude();
+        let direction = offset / distance;
+        self.lights
+            .iter()
+            .position(|light| light.pdf_li(&from.point, &direction, distance) > 0.)
+    }
+
+    /// Solid angle density of the infinite lights for light arriving along
+    /// the unit `direction`
+    fn infinite_light_density(&self, point: &Vector3, direction: &Vector3) -> f64 {
+        let density: f64 = self
+            .lights
+            .iter()
+            .filter(|light| light.is_infinite())
+            .map(|light| light.pdf_li(point, direction, f64::INFINITY))
+            .sum();
+        density * self.light_choice_pdf()
+    }
+
+    /// Turns a solid angle density at `from` into an area density at `to`
+    fn convert_density(&self, from: &Vertex, pdf: f64, to: &Vertex) -> f64 {
+        if self.is_infinite_light(to) {
+            return pdf;
+        }
+        let offset = to.point - from.point;
+        let distance2 = offset.magnitude_squared();
+        if distance2 == 0. {
+            return 0.;
+        }
+        let mut pdf = pdf / distance2;
+        if to.is_on_surface() {
+            pdf *= Vector3::dot(&to.normal, &(offset / distance2.sqrt())).abs();
+        }
+        pdf
+    }
+
+    /// Area density of `next` when sampled from the light vertex `vertex`
+    fn pdf_light(&self, vertex: &Vertex, next: &Vertex) -> f64 {
+        let direction = vertex.direction_to(next);
+        let cos_next = if next.is_on_surface() {
+            Vector3::dot(&next.normal, &direction).abs()
+        } else {
+            1.
+        };
+
+        if self.is_infinite_light(vertex) {
+            let infinite: Vec<_> = self
+                .lights
+                .iter()
+                .filter(|light| light.is_infinite())
+                .collect();
+            if infinite.is_empty() {
+                return 0.;
+            }
+            let pdf_position: f64 = infinite
+                .iter()
+                .map(|light| light.pdf_le(&next.point, &direction).0)
+                .sum();
+            return pdf_position / infinite.len() as f64 * cos_next;
+        }
+
+        let Some(index) = self.emitter_light(vertex, next) else {
+            return 0.;
+        };
+        let (_, pdf_direction) = self.lights[index].pdf_le(&vertex.point, &direction);
+        pdf_direction / (next.point - vertex.point).magnitude_squared() * cos_next
+    }
+
+    /// Density of `vertex` as the start of a light subpath heading to `next`
+    fn pdf_light_origin(&self, vertex: &Vertex, next: &Vertex) -> f64 {
+        let direction = vertex.direction_to(next);
+        if self.is_infinite_light(vertex) {
+            return self.infinite_light_density(&next.point, &-direction);
+        }
+        let Some(index) = self.emitter_light(vertex, next) else {
+            return 0.;
+        };
+        let (pdf_position, _) = self.lights[index].pdf_le(&vertex.point, &direction);
+        pdf_position * self.light_choice_pdf()
+    }
+
+    /// Area density of sampling `next` from `vertex` reached from `prev`
+    fn pdf(&self, vertex: &Vertex, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        let wn = vertex.direction_to(next);
+        let pdf = match &vertex.kind {
+            VertexKind::Light { .. } | VertexKind::Infinite => return self.pdf_light(vertex, next),
+            VertexKind::Camera => self.camera.pdf_we(&Ray::of(vertex.point, wn)).1,
+            VertexKind::Surface(hit) => {
+                let Some(prev) = prev else {
+                    return 0.;
+                };
+                hit.material.pdf(hit, &vertex.direction_to(prev), &wn)
+            }
+        };
+        self.convert_density(vertex, pdf, next)
+    }
+
+    fn is_visible(&self, world: &HitTarget, from: &Vector3, to: &Vector3) -> bool {
+        let offset = *to - *from;
+        let distance = offset.magnitude();
+        world
+            .hit(
+                &Ray::of(*from, offset / distance),
+                (0.001, distance - 0.001),
+            )
+            .is_none()
+    }
+
+    /// Geometric term between two vertices, zero when they cannot see each other
+    fn g(&self, world: &HitTarget, a: &Vertex, b: &Vertex) -> f64 {
+        let offset = b.point - a.point;
+        let distance2 = offset.magnitude_squared();
+        let direction = offset / distance2.sqrt();
+        let mut g = 1. / distance2;
+        if a.is_on_surface() {
+            g *= Vector3::dot(&a.normal, &direction).abs();
+        }
+        if b.is_on_surface() {
+            g *= Vector3::dot(&b.normal, &direction).abs();
+        }
+        if g == 0. || !self.is_visible(world, &a.point, &b.point) {
+            return 0.;
+        }
+        g
+    }
+
+    /// Extends `path` by following material samples from `origin` along the
+    /// unit `direction`, sampled with solid angle density `pdf`
+    #[allow(clippy::too_many_arguments)]
+    fn random_walk(
+        &self,
+        world: &HitTarget,
+        origin: Vector3,
+        direction: Vector3,
+        mut beta: Color,
+        pdf: f64,
+        max_vertices: u32,
+        from_camera: bool,
+        path: &mut Vec<Vertex>,
+    ) {
+        let mut ray = Ray::of(origin, direction);
+        let mut pdf_fwd = pdf;
+        for _ in 0..max_vertices {
+            let prev = path.len() - 1;
+            let wo = -ray.direction().normalize();
+            let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) else {
+                if from_camera {
+                    let mut vertex =
+                        Vertex::endpoint(VertexKind::Infinite, *ray.origin() - wo, beta);
+                    vertex.wo = wo;
+                    vertex.pdf_fwd = pdf_fwd;
+                    path.push(vertex);
+                }
+                break;
+            };
+
+            let mut vertex = Vertex {
+                kind: VertexKind::Surface(Box::new(hit.clone())),
+                point: hit.point,
+                normal: hit.normal,
+                wo,
+                beta,
+                pdf_fwd: 0.,
+                pdf_rev: 0.,
+                delta: false,
+            };
+            vertex.pdf_fwd = self.convert_density(&path[prev], pdf_fwd, &vertex);
+            path.push(vertex);
+
+            let u = (Random::f64(), Random::f64());
+            let Some(sample) = hit.material.sample(&hit, &wo, Random::f64(), u) else {
+                break;
+            };
+            if sample.pdf <= 0. || sample.f.is_near_zero() {
+                break;
+            }
+            beta = beta * sample.weight(&hit);
+            pdf_fwd = sample.pdf;
+            let mut pdf_rev = hit.material.pdf(&hit, &sample.wi, &wo);
+            let current = path.len() - 1;
+            if sample.flags.is_specular() {
+                path[current].delta = true;
+                pdf_fwd = 0.;
+                pdf_rev = 0.;
+            }
+            path[prev].pdf_rev = self.convert_density(&path[current], pdf_rev, &path[prev]);
+            ray = Ray::of(hit.point, sample.wi);
+        }
+    }
+
+    fn camera_subpath(&self, ray: &Ray, world: &HitTarget) -> Vec<Vertex> {
+        let direction = ray.direction().normalize();
+        let (_, pdf_direction) = self.camera.pdf_we(&Ray::of(*ray.origin(), direction));
+        let mut path = vec![Vertex::endpoint(
+            VertexKind::Camera,
+            *ray.origin(),
+            Color::white(),
+        )];
+        self.random_walk(
+            world,
+            *ray.origin(),
+            direction,
+            Color::white(),
+            pdf_direction,
+            self.max_depth + 1,
+            true,
+            &mut path,
+        );
+        path
+    }
+
+    fn light_subpath(&self, world: &HitTarget) -> Vec<Vertex> {
+        if self.lights.is_empty() {
+            return vec![];
+        }
+        let index =
+            ((Random::f64() * self.lights.len() as f64) as usize).min(self.lights.len() - 1);
+        let light = &self.lights[index];
+        let choice = self.light_choice_pdf();
+        let Some(emission) = light.sample_le(
+            (Random::f64(), Random::f64()),
+            (Random::f64(), Random::f64()),
+        ) else {
+            return vec![];
+        };
+        if emission.pdf_position <= 0.
+            || emission.pdf_direction <= 0.
+            || emission.radiance.is_near_zero()
+        {
+            return vec![];
+        }
+
+        let mut start = Vertex::endpoint(
+            VertexKind::Light {
+                index,
+                delta: emission.delta,
+            },
+            emission.origin,
+            emission.radiance / (emission.pdf_position * choice),
+        );
+        if !emission.delta {
+            start.normal = emission.normal;
+        }
+        start.pdf_fwd = emission.pdf_position * choice;
+
+        let cos_theta = Vector3::dot(&emission.normal, &emission.direction).abs();
+        let beta = emission.radiance * cos_theta
+            / (choice * emission.pdf_position * emission.pdf_direction);
+        let mut path = vec![start];
+        self.random_walk(
+            world,
+            emission.origin,
+            emission.direction,
+            beta,
+            emission.pdf_direction,
+            self.max_depth,
+            false,
+            &mut path,
+        );
+
+        // ? Paths from infinite lights start on a disk facing the scene,
+        // their first hit is measured by that disk's area density
+        if light.is_infinite() {
+            if path.len() > 1 {
+                path[1].pdf_fwd = emission.pdf_position;
+                if path[1].is_on_surface() {
+                    path[1].pdf_fwd *= Vector3::dot(&emission.direction, &path[1].normal).abs();
+                }
+            }
+            path[0].pdf_fwd = self.infinite_light_density(&emission.origin, &-emission.direction);
+        }
+        path
+    }
+
+    /// Contribution of the strategy joining `s` light vertices with `t`
+    /// camera vertices, with the image position for `t == 1`
+    fn connect(
+        &self,
+        world: &HitTarget,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+    ) -> Option<(Color, Option<(f64, f64)>)> {
+        if t > 1 && s != 0 && matches!(camera_path[t - 1].kind, VertexKind::Infinite) {
+            return None;
+        }
+
+        let mut sampled = None;
+        let mut uv = None;
+        // ? Lights at infinity that are not the background, like the sun,
+        // can only be reached by sampling them directly
+        let mut only_strategy = false;
+        let contribution = if s == 0 {
+            let pt = &camera_path[t - 1];
+            if !self.is_light(pt) {
+                return None;
+            }
+            only_strategy = self.pdf_light_origin(pt, &camera_path[t - 2]) == 0.;
+            self.le(pt, &camera_path[t - 2]) * pt.beta
+        } else if t == 1 {
+            let qs = &light_path[s - 1];
+            let sample = self
+                .camera
+                .sample_wi(&qs.point, (Random::f64(), Random::f64()))?;
+            if sample.pdf <= 0. || sample.importance <= 0. {
+                return None;
+            }
+            let camera = Vertex::endpoint(
+                VertexKind::Camera,
+                sample.lens_point,
+                Color::white() * (sample.importance / sample.pdf),
+            );
+            let mut contribution = qs.beta * qs.f(&camera) * camera.beta;
+            if qs.is_on_surface() {
+                contribution *= Vector3::dot(&sample.direction, &qs.normal).abs();
+            }
+            if contribution.is_near_zero() || !self.is_visible(world, &qs.point, &sample.lens_point)
+            {
+                return None;
+            }
+            uv = Some(sample.uv);
+            sampled = Some(camera);
+            contribution
+        } else if s == 1 {
+            let pt = &camera_path[t - 1];
+            if self.lights.is_empty() {
+                return None;
+            }
+            let index =
+                ((Random::f64() * self.lights.len() as f64) as usize).min(self.lights.len() - 1);
+            let light = &self.lights[index];
+            let sample = light.sample_li(&pt.point, (Random::f64(), Random::f64()))?;
+            if sample.pdf <= 0. || sample.radiance.is_near_zero() {
+                return None;
+            }
+            let distance = if sample.distance.is_finite() {
+                sample.distance
+            } else {
+                1.
+            };
+            let mut vertex = Vertex::endpoint(
+                VertexKind::Light {
+                    index,
+                    delta: sample.delta,
+                },
+                pt.point + distance * sample.direction,
+                sample.radiance / (sample.pdf * self.light_choice_pdf()),
+            );
+            vertex.normal = sample.normal;
+            vertex.pdf_fwd = self.pdf_light_origin(&vertex, pt);
+            only_strategy = sample.distance.is_infinite() && !light.is_infinite();
+
+            let mut contribution = pt.beta * pt.f(&vertex) * vertex.beta;
+            if pt.is_on_surface() {
+                contribution *= Vector3::dot(&sample.direction, &pt.normal).abs();
+            }
+            if contribution.is_near_zero() || !sample.is_visible(world, &pt.point) {
+                return None;
+            }
+            sampled = Some(vertex);
+            contribution
+        } else {
+            let qs = &light_path[s - 1];
+            let pt = &camera_path[t - 1];
+            let contribution = qs.beta * qs.f(pt) * pt.f(qs) * pt.beta;
+            if contribution.is_near_zero() {
+                return None;
+            }
+            contribution * self.g(world, qs, pt)
+        };
+
+        if contribution.is_near_zero() {
+            return None;
+        }
+        let weight = if only_strategy {
+            1.
+        } else {
+            self.mis_weight(light_path, camera_path, sampled.as_ref(), s, t)
+        };
+        let contribution = contribution * weight;
+        if !contribution.to_array().iter().all(|c| c.is_finite()) {
+            return None;
+        }
+        Some((contribution, uv))
+    }
+
+    /// Power of one heuristic over every strategy that could have produced
+    /// the same path, computed from the ratios of their vertex densities
+    fn mis_weight(
+        &self,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.;
+        }
+
+        let sampled_light = if s == 1 { sampled } else { None };
+        let sampled_camera = if t == 1 { sampled } else { None };
+        let qs = (s > 0).then(|| sampled_light.unwrap_or(&light_path[s - 1]));
+        let pt = sampled_camera.unwrap_or(&camera_path[t - 1]);
+        let qs_minus = (s > 1).then(|| &light_path[s - 2]);
+        let pt_minus = (t > 1).then(|| &camera_path[t - 2]);
+
+        let mut light: Vec<Density> = light_path[..s].iter().map(Density::from).collect();
+        let mut camera: Vec<Density> = camera_path[..t].iter().map(Density::from).collect();
+        if let Some(vertex) = sampled_light {
+            light[0] = vertex.into();
+        }
+        if let Some(vertex) = sampled_camera {
+            camera[0] = vertex.into();
+        }
+
+        // ? The connection vertices are not degenerate and their reverse
+        // densities now follow the connection
+        camera[t - 1].delta = false;
+        camera[t - 1].rev = match (qs, pt_minus) {
+            (Some(qs), _) => self.pdf(qs, qs_minus, pt),
+            (None, Some(pt_minus)) => self.pdf_light_origin(pt, pt_minus),
+            (None, None) => 0.,
+        };
+        if let Some(pt_minus) = pt_minus {
+            camera[t - 2].rev = match qs {
+                Some(qs) => self.pdf(pt, Some(qs), pt_minus),
+                None => self.pdf_light(pt, pt_minus),
+            };
+        }
+        // ? Unlike the zero densities around specular vertices, a light that
+        // cannot emit towards the camera subpath rules out every strategy
+        // with a longer light subpath, and an emitter that no light samples
+        // (such as the background without lights) rules out all of them
+        let unreachable = match (s, pt_minus) {
+            (0 | 1, _) if camera[t - 1].rev == 0. => Some(t - 1),
+            (0, Some(_)) if camera[t - 2].rev == 0. => Some(t - 2),
+            _ => None,
+        };
+        if let Some(qs) = qs {
+            light[s - 1].delta = false;
+            light[s - 1].rev = self.pdf(pt, pt_minus, qs);
+            if let Some(qs_minus) = qs_minus {
+                light[s - 2].rev = self.pdf(qs, Some(pt), qs_minus);
+            }
+        }
+
+        let remap = |pdf: f64| if pdf != 0. { pdf } else { 1. };
+        let mut sum = 0.;
+        let mut ratio = 1.;
+        for i in (1..t).rev() {
+            if unreachable == Some(i) {
+                break;
+            }
+            ratio *= remap(camera[i].rev) / remap(camera[i].fwd);
+            if !camera[i].delta && !camera[i - 1].delta {
+                sum += ratio;
+            }
+        }
+        ratio = 1.;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].rev) / remap(light[i].fwd);
+            let delta_light = if i > 0 {
+                light[i - 1].delta
+            } else {
+                let start = sampled_light.unwrap_or(&light_path[0]);
+                matches!(start.kind, VertexKind::Light { delta: true, .. })
+            };
+            if !light[i].delta && !delta_light {
+                sum += ratio;
+            }
+        }
+        1. / (1. + sum)
+    }
+}
+
+impl Integrator for Bdpt {
+    fn radiance(&self, ray: &Ray, world: &HitTarget) -> Color {
+        let camera_path = self.camera_subpath(ray, world);
+        let light_path = self.light_subpath(world);
+
+        let mut color = Color::black();
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                let depth = (s + t) as i64 - 2;
+                if (s == 1 && t == 1) || depth < 0 || depth > self.max_depth as i64 {
+                    continue;
+                }
+                let Some((contribution, uv)) = self.connect(world, &light_path, &camera_path, s, t)
+                else {
+                    continue;
+                };
+                match uv {
+                    Some(uv) => self.film.splat(uv, contribution),
+                    None => color += contribution,
+                }
+            }
+        }
+        color
+    }
+
+    fn splats(&self, samples: u32) -> Option<Vec<Color>> {
+        Some(self.film.image(samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{
+            geometry::sphere::Sphere,
+            light::{PointLight, SphereLight},
+            material::{color::Color, lambertian::Lambertian},
+        },
+        render::{
+            film,
+            integrator::{NextEventEstimation, PathTracer},
+        },
+        util::random::Random,
+        vec3,
+    };
+
+    #[test]
+    fn background_without_lights_matches_path() {
+        let mut world = HitTarget::new();
+        world.push(Arc::new(Sphere::new(
+            Vector3::zero(),
+            1.,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )));
+        let camera = Camera::new(
+            vec3!(0, 0, 4),
+            Vector3::zero(),
+            Vector3::up(),
+            40.,
+            1.,
+            0.,
+            4.,
+        );
+        let bdpt = Bdpt::new(camera, vec![], 4, 1, 1);
+        let path = PathTracer::new(4).with_min_depth(4);
+
+        let ray = || Ray::of(vec3!(0, 0, 4), vec3!(0, 0.2, -1).normalize());
+        let samples = 20000;
+        let mean = |integrator: &dyn Integrator| {
+            (0..samples).fold(Color::black(), |sum, _| {
+                sum + integrator.radiance(&ray(), &world)
+            }) / samples as f64
+        };
+        let (bdpt, path) = (mean(&bdpt), mean(&path));
+        for (b, p) in bdpt.to_array().into_iter().zip(path.to_array()) {
+            assert!(b.is_finite() && b > 0.);
+            assert!((b - p).abs() < 0.05 * p, "bdpt {b} path {p}");
+        }
+    }
+
+    #[test]
+    fn pixel_with_splats_matches_next_event_estimation() {
+        let area = SphereLight::new(vec3!(0, 5, 3), 2., Color::white());
+        let mut world = HitTarget::new();
+        world.push(Arc::new(area.sphere()));
+        world.push(Arc::new(Sphere::new(
+            Vector3::zero(),
+            1.,
+            Arc::new(Lambertian::new(Color::new(0.8, 0.5, 0.2))),
+        )));
+        let lights: Vec<Arc<dyn Light>> = vec![
+            Arc::new(area),
+            Arc::new(PointLight::new(vec3!(2, 1, 3), Color::white() * 4.)),
+        ];
+        // ? A single wide pixel, so light paths reaching the camera all
+        // splat onto the pixel being compared
+        let camera = Camera::new(
+            vec3!(0, 0, 4),
+            Vector3::zero(),
+            Vector3::up(),
+            40.,
+            1.,
+            0.,
+            3.,
+        );
+        let black = Background::Uniform(Color::black());
+        let bdpt = Bdpt::new(camera.clone(), lights.clone(), 4, 1, 1).with_background(black);
+        let nee = NextEventEstimation::new(lights, 4).with_background(black);
+
+        let samples = 150000;
+        let mean = |integrator: &dyn Integrator| {
+            (0..samples).fold(Color::black(), |sum, _| {
+                let (u, v) = film::pixel_uv(0, 0, (Random::f64(), Random::f64()), 1, 1);
+                sum + integrator.radiance(&camera.get_ray(u, v), &world)
+            }) / samples as f64
+        };
+        let nee = mean(&nee);
+        let radiance = mean(&bdpt);
+        let splat = bdpt.splats(samples).unwrap()[0];
+        assert!(splat.x() > 0.02 * radiance.x(), "{splat:?} {radiance:?}");
+        let bdpt = radiance + splat;
+        for (b, n) in bdpt.to_array().into_iter().zip(nee.to_array()) {
+            assert!(n > 0. && (b - n).abs() < 0.03 * n, "bdpt {b} nee {n}");
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    object::{geometry::vector::Vector3, material::color::Color},
+    render::film,
+    view::{camera::Camera, ray::HitTarget},
+};
+
+/// Per pixel geometry of the primary hits, for screen space passes
+pub struct GBuffer {
+    width: usize,
+    height: usize,
+    /// Distance from the camera, infinite where nothing was hit
+    depth: Vec<f64>,
+    /// Shading normal facing the camera, zero where nothing was hit
+    normal: Vec<Vector3>,
+    /// Index of the hit object in the world
+    object_id: Vec<Option<usize>>,
+    /// Ambient color of the hit material, black where nothing was hit
+    ambient: Vec<Color>,
+}
+
+impl GBuffer {
+    /// Traces one ray through the center of each pixel, rows from the top
+    pub fn render(camera: &Camera, world: &HitTarget, width: usize, height: usize) -> Self {
+        let mut buffer = Self {
+            width,
+            height,
+            depth: vec![f64::INFINITY; width * height],
+            normal: vec![Vector3::zero(); width * height],
+            object_id: vec![None; width * height],
+            ambient: vec![Color::black(); width * height],
+        };
+
+        for j in 0..height {
+            for i in 0..width {
+                let (u, v) = film::pixel_uv(i, j, (0.5, 0.5), width, height);
+                let ray = camera.get_ray(u, v);
+                if let Some((index, hit)) = world.hit_indexed(&ray, (0.001, f64::INFINITY)) {
+                    let pixel = j * width + i;
+                    buffer.depth[pixel] = hit.distance;
+                    buffer.normal[pixel] = hit.normal;
+                    buffer.object_id[pixel] = Some(index);
+                    buffer.ambient[pixel] = hit.material.ambient(&hit);
+                }
+            }
+        }
+        buffer
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self, i: usize, j: usize) -> f64 {
+        self.depth[j * self.width + i]
+    }
+
+    pub fn normal(&self, i: usize, j: usize) -> Vector3 {
+        self.normal[j * self.width + i]
+    }
+
+    pub fn object_id(&self, i: usize, j: usize) -> Option<usize> {
+        self.object_id[j * self.width + i]
+    }
+
+    pub fn ambient(&self, i: usize, j: usize) -> Color {
+        self.ambient[j * self.width + i]
+    }
+
+    /// Adds the ambient color of the hit materials to row major `pixels`
+    pub fn add_ambient(&self, pixels: &mut [Color]) {
+        for (pixel, ambient) in pixels.iter_mut().zip(self.ambient.iter()) {
+            *pixel += *ambient;
+        }
+    }
+}
+
+/// Detects silhouettes and creases from discontinuities in a [`GBuffer`]
+#[derive(Debug, Copy, Clone)]
+pub struct Outline {
+    /// Relative depth jump between neighbours that counts as an edge
+    pub depth_threshold: f64,
+    /// Angle in degrees between neighbouring normals that counts as a crease
+    pub crease_angle: f64,
+    pub color: Color,
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self {
+            depth_threshold: 0.1,
+            crease_angle: 45.,
+            color: Color::black(),
+        }
+    }
+}
+
+impl Outline {
+    fn is_edge(&self, buffer: &GBuffer, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (id_a, id_b) = (buffer.object_id(a.0, a.1), buffer.object_id(b.0, b.1));
+        if id_a != id_b {
+            return true;
+        }
+        if id_a.is_none() {
+            return false;
+        }
+
+        let (depth_a, depth_b) = (buffer.depth(a.0, a.1), buffer.depth(b.0, b.1));
+        if (depth_a - depth_b).abs() > self.depth_threshold * depth_a.min(depth_b) {
+            return true;
+        }
+        let cos_angle = Vector3::dot(&buffer.normal(a.0, a.1), &buffer.normal(b.0, b.1));
+        cos_angle < self.crease_angle.to_radians().cos()
+    }
+
+    /// Edge mask with a row major pixel per entry
+    pub fn edges(&self, buffer: &GBuffer) -> Vec<bool> {
+        let (width, height) = (buffer.width(), buffer.height());
+        let mut edges = vec![false; width * height];
+        for j in 0..height {
+            for i in 0..width {
+                // ? Both pixels across a discontinuity are marked so lines
+                // are two pixels wide and centered on the edge
+                if i + 1 < width && self.is_edge(buffer, (i, j), (i + 1, j)) {
+                    edges[j * width + i] = true;
+                    edges[j * width + i + 1] = true;
+                }
+                if j + 1 < height && self.is_edge(buffer, (i, j), (i, j + 1)) {
+                    edges[j * width + i] = true;
+                    edges[(j + 1) * width + i] = true;
+                }
+            }
+        }
+        edges
+    }
+
+    /// Paints the outline color over row major `pixels`
+    pub fn draw(&self, buffer: &GBuffer, pixels: &mut [Color]) {
+        for (pixel, edge) in pixels.iter_mut().zip(self.edges(buffer)) {
+            if edge {
+                *pixel = self.color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        object::{geometry::sphere::Sphere, material::lambertian::Lambertian},
+        vec3,
+    };
+
+    #[test]
+    fn outlines_silhouettes_only() {
+        let mut world = HitTarget::new();
+        world.push(Arc::new(Sphere::new(
+            Vector3::zero(),
+            1.,
+            Arc::new(Lambertian::new(Color::white())),
+        )));
+        let camera = Camera::new(
+            vec3!(0, 0, 5),
+            Vector3::zero(),
+            Vector3::up(),
+            40.,
+            1.,
+            0.,
+            5.,
+        );
+        let size = 31;
+        let buffer = GBuffer::render(&camera, &world, size, size);
+        let edges = Outline::default().edges(&buffer);
+
+        let center = size / 2;
+        assert_eq!(buffer.object_id(center, center), Some(0));
+        assert_eq!(buffer.object_id(0, 0), None);
+        assert!(!edges[center * size + center] && !edges[0]);
+        // ? Walking out from the center crosses the silhouette exactly once
+        let row: Vec<bool> = (center..size).map(|i| edges[center * size + i]).collect();
+        let first = row.iter().position(|&edge| edge).unwrap();
+        let last = row.iter().rposition(|&edge| edge).unwrap();
+        assert_eq!(last, first + 1, "{row:?}");
+        assert!(buffer.object_id(center + first, center).is_some());
+        assert!(buffer.object_id(center + last, center).is_none());
+    }
+}
@@ -0,0 +1,311 @@
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul};
+
+use crate::object::material::color::Color;
+
+/// Wavelengths carried by each path, the first one is the hero
+pub const SAMPLES: usize = 4;
+pub const LAMBDA_MIN: f64 = 360.;
+pub const LAMBDA_MAX: f64 = 830.;
+
+/// Integral of the CIE `ȳ` matching function over the visible range
+const CIE_Y_INTEGRAL: f64 = 106.856895;
+
+/// Linear sRGB from CIE XYZ, for a D65 white point
+const XYZ_TO_SRGB: [[f64; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.9692660, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+/// Radiance or reflectance at each of the path's wavelengths
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SampledSpectrum([f64; SAMPLES]);
+
+impl SampledSpectrum {
+    pub fn constant(value: f64) -> Self {
+        Self([value; SAMPLES])
+    }
+
+    pub fn zero() -> Self {
+        Self::constant(0.)
+    }
+
+    /// Upsamples a linear RGB reflectance with Smits' method
+    pub fn from_rgb(color: Color, wavelengths: &SampledWavelengths) -> Self {
+        Self(wavelengths.lambda.map(|lambda| smits(color, lambda)))
+    }
+}
+
+impl Index<usize> for SampledSpectrum {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for SampledSpectrum {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for SampledSpectrum {
+    fn add_assign(&mut self, rhs: Self) {
+        for i in 0..SAMPLES {
+            self.0[i] += rhs.0[i];
+        }
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(mut self, rhs: Self) -> Self::Output {
+        for i in 0..SAMPLES {
+            self.0[i] *= rhs.0[i];
+        }
+        self
+    }
+}
+
+/// Hero wavelength sampling (Wilkie et al. 2014): one uniform wavelength
+/// and the others evenly spaced after it across the visible range
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SampledWavelengths {
+    lambda: [f64; SAMPLES],
+    pdf: [f64; SAMPLES],
+}
+
+impl SampledWavelengths {
+    pub fn sample_uniform(u: f64) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = LAMBDA_MIN + u * range;
+        let mut lambda = [hero; SAMPLES];
+        for (i, lambda) in lambda.iter_mut().enumerate().skip(1) {
+            *lambda += i as f64 * range / SAMPLES as f64;
+            if *lambda > LAMBDA_MAX {
+                *lambda -= range;
+            }
+        }
+        Self {
+            lambda,
+            pdf: [1. / range; SAMPLES],
+        }
+    }
+
+    /// Importance samples the wavelengths the eye is most sensitive to
+    /// (Radziszewski et al. 2009), with stratified offsets per wavelength
+    pub fn sample_visible(u: f64) -> Self {
+        let mut lambda = [0.; SAMPLES];
+        let mut pdf = [0.; SAMPLES];
+        for i in 0..SAMPLES {
+            let u = (u + i as f64 / SAMPLES as f64).fract();
+            lambda[i] = 538. - 138.888889 * (0.85691062 - 1.82750197 * u).atanh();
+            pdf[i] = 0.0039398042 / (0.0072 * (lambda[i] - 538.)).cosh().powi(2);
+        }
+        Self { lambda, pdf }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self, index: usize) -> f64 {
+        self.lambda[index]
+    }
+
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.)
+    }
+
+    /// Drops the secondary wavelengths after a wavelength dependent event,
+    /// leaving the hero to carry the whole estimate
+    pub fn terminate_secondary(&mut self) {
+        if self.is_secondary_terminated() {
+            return;
+        }
+        for pdf in self.pdf[1..].iter_mut() {
+            *pdf = 0.;
+        }
+        self.pdf[0] /= SAMPLES as f64;
+    }
+
+    /// Monte Carlo estimate of the CIE XYZ color of `spectrum`
+    pub fn to_xyz(&self, spectrum: &SampledSpectrum) -> [f64; 3] {
+        let mut xyz = [0.; 3];
+        for i in 0..SAMPLES {
+            if self.pdf[i] == 0. {
+                continue;
+            }
+            let matching = cie_xyz(self.lambda[i]);
+            for (channel, value) in xyz.iter_mut().enumerate() {
+                *value += matching[channel] * spectrum[i] / self.pdf[i];
+            }
+        }
+        xyz.map(|value| value / (SAMPLES as f64 * CIE_Y_INTEGRAL))
+    }
+
+    /// Linear sRGB of `spectrum`, white balanced so a constant spectrum of
+    /// one maps to white
+    pub fn to_rgb(&self, spectrum: &SampledSpectrum) -> Color {
+        let xyz = self.to_xyz(spectrum);
+        let mut rgb = Color::zero();
+        for (i, row) in XYZ_TO_SRGB.iter().enumerate() {
+            let white: f64 = row.iter().sum();
+            rgb[i] = (row[0] * xyz[0] + row[1] * xyz[1] + row[2] * xyz[2]) / white;
+        }
+        rgb
+    }
+}
+
+/// CIE 1931 matching functions from the multi-lobe fit of Wyman et al. 2013
+pub fn cie_xyz(lambda: f64) -> [f64; 3] {
+    let gaussian = |mu: f64, sigma_below: f64, sigma_above: f64| {
+        let sigma = if lambda < mu {
+            sigma_below
+        } else {
+            sigma_above
+        };
+        let t = (lambda - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    [
+        1.056 * gaussian(599.8, 37.9, 31.0) + 0.362 * gaussian(442.0, 16.0, 26.7)
+            - 0.065 * gaussian(501.1, 20.4, 26.2),
+        0.821 * gaussian(568.8, 46.9, 40.5) + 0.286 * gaussian(530.9, 16.3, 31.1),
+        1.217 * gaussian(437.0, 11.8, 36.0) + 0.681 * gaussian(459.0, 26.0, 13.8),
+    ]
+}
+
+/// Basis spectra of Smits 1999, sampled in ten bins from 380 to 720 nm
+const SMITS_WHITE: [f64; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+fn smits(color: Color, lambda: f64) -> f64 {
+    let position = ((lambda - 380.) / (720. - 380.) * 9.).clamp(0., 9.);
+    let bin = (position as usize).min(8);
+    let t = position - bin as f64;
+    let basis = |spectrum: &[f64; 10]| spectrum[bin] * (1. - t) + spectrum[bin + 1] * t;
+
+    let (r, g, b) = (color.x(), color.y(), color.z());
+    // ? The smallest channel is white, the middle one a secondary color
+    // and the rest a primary
+    if r <= g && r <= b {
+        r * basis(&SMITS_WHITE)
+            + if g <= b {
+                (g - r) * basis(&SMITS_CYAN) + (b - g) * basis(&SMITS_BLUE)
+            } else {
+                (b - r) * basis(&SMITS_CYAN) + (g - b) * basis(&SMITS_GREEN)
+            }
+    } else if g <= r && g <= b {
+        g * basis(&SMITS_WHITE)
+            + if r <= b {
+                (r - g) * basis(&SMITS_MAGENTA) + (b - r) * basis(&SMITS_BLUE)
+            } else {
+                (b - g) * basis(&SMITS_MAGENTA) + (r - b) * basis(&SMITS_RED)
+            }
+    } else {
+        b * basis(&SMITS_WHITE)
+            + if r <= g {
+                (r - b) * basis(&SMITS_YELLOW) + (g - r) * basis(&SMITS_GREEN)
+            } else {
+                (g - b) * basis(&SMITS_YELLOW) + (r - g) * basis(&SMITS_RED)
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Averages `to_rgb` over stratified wavelength samples
+    fn average_rgb(spectrum: impl Fn(&SampledWavelengths) -> SampledSpectrum) -> Color {
+        let count = 10_000;
+        let mut sum = Color::zero();
+        for i in 0..count {
+            let wavelengths = SampledWavelengths::sample_visible((i as f64 + 0.5) / count as f64);
+            sum += wavelengths.to_rgb(&spectrum(&wavelengths));
+        }
+        sum / count
+    }
+
+    #[test]
+    fn constant_spectrum_is_white() {
+        let rgb = average_rgb(|_| SampledSpectrum::constant(1.));
+        assert!(
+            (rgb - Color::white())
+                .to_array()
+                .iter()
+                .all(|c| c.abs() < 0.02),
+            "{rgb:?}"
+        );
+    }
+
+    #[test]
+    fn upsampled_colors_round_trip() {
+        // ? Smits' basis spectra are a fit, saturated reds come back a few
+        // percent darker
+        for color in [
+            Color::new(0.8, 0.2, 0.1),
+            Color::new(0.1, 0.5, 0.3),
+            Color::new(0.2, 0.3, 0.9),
+        ] {
+            let rgb = average_rgb(|wavelengths| SampledSpectrum::from_rgb(color, wavelengths));
+            assert!(
+                (rgb - color).to_array().iter().all(|c| c.abs() < 0.08),
+                "{color:?} became {rgb:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn terminating_secondaries_keeps_the_hero_unbiased() {
+        // ? With one wavelength left it stands in for all of them, so a
+        // constant spectrum still averages to white
+        let count = 10_000;
+        let mut sum = Color::zero();
+        for i in 0..count {
+            let mut wavelengths =
+                SampledWavelengths::sample_visible((i as f64 + 0.5) / count as f64);
+            wavelengths.terminate_secondary();
+            sum += wavelengths.to_rgb(&SampledSpectrum::constant(1.));
+        }
+        let rgb = sum / count;
+        assert!(
+            (rgb - Color::white())
+                .to_array()
+                .iter()
+                .all(|c| c.abs() < 0.02),
+            "{rgb:?}"
+        );
+    }
+}
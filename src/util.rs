@@ -4,9 +4,15 @@ pub mod random;
 
 use crate::{
     object::{geometry::vector::Vector3, material::color::Color},
+    render::{
+        integrator::{HemispherePathTracer, Integrator, Normals, PathTracer, SpectralPathTracer},
+        spectral::{SampledSpectrum, SampledWavelengths},
+    },
     view::ray::{Hit, HitTarget, Ray},
 };
 
+use self::random::Random;
+
 pub fn print_color(mut pixel_color: Color) {
     pixel_color *= 255.999;
     println!(
@@ -44,47 +50,28 @@ fn hit_sphere(center: Vector3, radius: f64, ray: &Ray) -> f64 {
 }
 
 pub fn ray_color(ray: &Ray, world: &HitTarget) -> Color {
-    if let Some(hit) = world.hit(&ray, (0., f64::INFINITY)) {
-        return 0.5 * (hit.normal + Color::ones());
-    }
-    let normalized_direction = ray.direction().normalize();
-    let t = 0.5 * (normalized_direction.y() + 1.);
-    (1. - t) * Color::ones() + t * Color::new(0.5, 0.7, 1)
+    Normals.radiance(ray, world)
 }
 
+/// Follows paths for up to `depth` bounces, Russian roulette may end them
+/// after [`DEFAULT_MIN_DEPTH`](crate::render::integrator::DEFAULT_MIN_DEPTH)
 pub fn ray_color_diffuse(ray: &Ray, world: &HitTarget, depth: u32) -> Color {
-    if depth == 0 {
-        return Color::black();
-    }
+    PathTracer::new(depth).radiance(ray, world)
+}
 
-    if let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) {
-        if let Some(scatter) = hit.material.scatter(&ray, &hit) {
-            return scatter.attenuation * ray_color_diffuse(&scatter.ray, &world, depth - 1);
-        }
-        return Color::black();
-    }
-    let normalized_direction = ray.direction().normalize();
-    let t = 0.5 * (normalized_direction.y() + 1.);
-    Vector3::lerp(&Color::ones(), &Color::new(0.5, 0.7, 1), t)
+/// Spectral counterpart of [`ray_color_diffuse`], following the hero
+/// wavelength through dispersive materials
+pub fn ray_color_spectral(
+    ray: &Ray,
+    world: &HitTarget,
+    depth: u32,
+    wavelengths: &mut SampledWavelengths,
+) -> SampledSpectrum {
+    SpectralPathTracer::new(depth).spectral_radiance(ray, world, wavelengths)
 }
 
 pub fn ray_color_diffuse_hemisphere(ray: &Ray, world: &HitTarget, depth: u32) -> Color {
-    if depth == 0 {
-        return Color::black();
-    }
-
-    if let Some(hit) = world.hit(&ray, (0.001, f64::INFINITY)) {
-        let diffuse_target = hit.point + hit.normal + Vector3::random_in_hemisphere(hit.normal);
-        return 0.5
-            * ray_color_diffuse(
-                &Ray::of(hit.point, diffuse_target - hit.point),
-                world,
-                depth - 1,
-            );
-    }
-    let normalized_direction = ray.direction().normalize();
-    let t = 0.5 * (normalized_direction.y() + 1.);
-    Vector3::lerp(&Color::ones(), &Color::new(0.5, 0.7, 1), t)
+    HemispherePathTracer::new(depth).radiance(ray, world)
 }
 
 pub trait Between<T> {
@@ -1,7 +1,24 @@
+use std::f64::consts::PI;
+
 use crate::object::geometry::vector::Vector3;
 
 use super::ray::Ray;
 
+/// Point on the lens seen from a point of the scene, see [`Camera::sample_wi`]
+#[derive(Debug, Copy, Clone)]
+pub struct CameraSample {
+    pub lens_point: Vector3,
+    /// Unit direction from the scene point towards the lens
+    pub direction: Vector3,
+    pub distance: f64,
+    /// Importance of the ray leaving the lens towards the scene point
+    pub importance: f64,
+    /// Solid angle density of `direction`
+    pub pdf: f64,
+    pub uv: (f64, f64),
+}
+
+#[derive(Clone)]
 pub struct Camera {
     // For perspective projection
     origin: Vector3,
@@ -16,6 +33,9 @@ pub struct Camera {
     right: Vector3,
     up: Vector3,
     lens_radius: f64,
+    /// Unit viewing direction
+    forward: Vector3,
+    focus_distance: f64,
 }
 
 impl Camera {
@@ -52,6 +72,8 @@ impl Camera {
             right,
             up,
             lens_radius,
+            forward: -front,
+            focus_distance,
         }
     }
 
@@ -67,4 +89,98 @@ impl Camera {
             self.lower_left_corner + u * self.horizontal + v * self.vertical - self.origin - offset,
         )
     }
+
+    /// Inverse of [`Camera::get_ray`] for a pinhole at the lens center,
+    /// returns the `(u, v)` of `point` or `None` if it is behind the camera
+    pub fn project(&self, point: &Vector3) -> Option<(f64, f64)> {
+        let plane_normal = Vector3::cross(&self.horizontal, &self.vertical);
+        let direction = *point - self.origin;
+        let denominator = Vector3::dot(&direction, &plane_normal);
+        let t = Vector3::dot(&(self.lower_left_corner - self.origin), &plane_normal) / denominator;
+        if denominator == 0. || t <= 0. {
+            return None;
+        }
+
+        let on_plane = self.origin + t * direction - self.lower_left_corner;
+        let u = Vector3::dot(&on_plane, &self.horizontal) / self.horizontal.magnitude_squared();
+        let v = Vector3::dot(&on_plane, &self.vertical) / self.vertical.magnitude_squared();
+        Some((u, v))
+    }
+
+    fn lens_area(&self) -> f64 {
+        if self.lens_radius > 0. {
+            PI * self.lens_radius * self.lens_radius
+        } else {
+            1.
+        }
+    }
+
+    /// `(u, v)` in the image of a ray leaving `lens_point` along the unit
+    /// `direction`, with the cosine to the viewing direction
+    fn film_position(
+        &self,
+        lens_point: &Vector3,
+        direction: &Vector3,
+    ) -> Option<((f64, f64), f64)> {
+        let cos_theta = Vector3::dot(direction, &self.forward);
+        if cos_theta <= 0. {
+            return None;
+        }
+        let on_plane =
+            *lens_point + (self.focus_distance / cos_theta) * *direction - self.lower_left_corner;
+        let u = Vector3::dot(&on_plane, &self.horizontal) / self.horizontal.magnitude_squared();
+        let v = Vector3::dot(&on_plane, &self.vertical) / self.vertical.magnitude_squared();
+        if !(0. ..=1.).contains(&u) || !(0. ..=1.).contains(&v) {
+            return None;
+        }
+        Some(((u, v), cos_theta))
+    }
+
+    /// Importance of a ray leaving the lens at an angle of `cos_theta` from
+    /// the viewing direction, normalized over the whole image
+    fn importance_at(&self, cos_theta: f64) -> f64 {
+        let viewport_area = self.horizontal.magnitude() * self.vertical.magnitude();
+        let distance2 = self.focus_distance * self.focus_distance;
+        distance2 / (viewport_area * self.lens_area() * cos_theta.powi(4))
+    }
+
+    /// Densities `(position, direction)` with which [`Camera::get_ray`]
+    /// produces `ray`, zero outside the image
+    pub fn pdf_we(&self, ray: &Ray) -> (f64, f64) {
+        let direction = ray.direction().normalize();
+        let Some((_, cos_theta)) = self.film_position(ray.origin(), &direction) else {
+            return (0., 0.);
+        };
+        let viewport_area = self.horizontal.magnitude() * self.vertical.magnitude();
+        let distance2 = self.focus_distance * self.focus_distance;
+        (
+            1. / self.lens_area(),
+            distance2 / (viewport_area * cos_theta.powi(3)),
+        )
+    }
+
+    /// Samples a point of the lens that sees `point`, `None` if `point` is
+    /// outside the image
+    pub fn sample_wi(&self, point: &Vector3, u: (f64, f64)) -> Option<CameraSample> {
+        let radius = self.lens_radius * u.0.sqrt();
+        let phi = 2. * PI * u.1;
+        let lens_point =
+            self.origin + radius * phi.cos() * self.right + radius * phi.sin() * self.up;
+
+        let offset = *point - lens_point;
+        let distance = offset.magnitude();
+        if distance == 0. {
+            return None;
+        }
+        let outgoing = offset / distance;
+        let (uv, cos_theta) = self.film_position(&lens_point, &outgoing)?;
+        Some(CameraSample {
+            lens_point,
+            direction: -outgoing,
+            distance,
+            importance: self.importance_at(cos_theta),
+            pdf: distance * distance / (cos_theta * self.lens_area()),
+            uv,
+        })
+    }
 }
@@ -3,11 +3,16 @@ use std::{
     sync::Arc,
 };
 
-use crate::object::{geometry::vector::Vector3, material::Material};
+use crate::object::{
+    geometry::{onb::Onb, vector::Vector3},
+    material::{color::Color, Material},
+};
 
 pub struct Ray {
     origin: Vector3,
     direction: Vector3,
+    /// Wavelength in nanometers carried by spectral paths
+    wavelength: Option<f64>,
 }
 
 impl Ray {
@@ -15,11 +20,21 @@ impl Ray {
         Self {
             origin: Vector3::zero(),
             direction: Vector3::zero(),
+            wavelength: None,
         }
     }
 
     pub fn of(origin: Vector3, direction: Vector3) -> Self {
-        Self { origin, direction }
+        Self {
+            origin,
+            direction,
+            wavelength: None,
+        }
+    }
+
+    pub fn with_wavelength(mut self, wavelength: f64) -> Self {
+        self.wavelength = Some(wavelength);
+        self
     }
 
     pub fn origin(&self) -> &Vector3 {
@@ -30,6 +45,10 @@ impl Ray {
         &self.direction
     }
 
+    pub fn wavelength(&self) -> Option<f64> {
+        self.wavelength
+    }
+
     pub fn at(&self, t: impl Into<f64>) -> Vector3 {
         let t: f64 = t.into();
         self.origin + t * self.direction
@@ -40,15 +59,67 @@ pub trait Hit: Sync + Send {
     fn hit(&self, ray: &Ray, range: (f64, f64)) -> Option<RayHit>;
 }
 
+#[derive(Clone)]
 pub struct RayHit {
     pub point: Vector3,
+    /// Shading normal, facing against the ray
     pub normal: Vector3,
+    /// True surface normal, facing against the ray
+    pub geometric_normal: Vector3,
+    /// Surface parameterization at the hit point
+    pub uv: (f64, f64),
+    /// Partial derivative of the point along u, or the unit vertex tangent
+    /// of meshes that have them, zero if unknown
+    pub tangent: Vector3,
+    /// Partial derivative of the point along v, or the unit bitangent of
+    /// meshes with vertex tangents, zero if unknown
+    pub bitangent: Vector3,
+    /// Interpolated vertex color, if the surface has any
+    pub color: Option<Color>,
     pub t: f64,
+    /// Distance travelled along the ray, `t` scaled by the direction length
+    pub distance: f64,
+    /// Wavelength carried by the ray in spectral mode
+    pub wavelength: Option<f64>,
     pub front_face: bool,
     pub material: Arc<dyn Material>,
 }
 
 impl RayHit {
+    pub fn new(ray: &Ray, t: f64, outward_normal: Vector3, material: Arc<dyn Material>) -> Self {
+        let mut hit = Self {
+            point: ray.at(t),
+            normal: outward_normal,
+            geometric_normal: outward_normal,
+            uv: (0., 0.),
+            tangent: Vector3::zero(),
+            bitangent: Vector3::zero(),
+            color: None,
+            t,
+            distance: t * ray.direction.magnitude(),
+            wavelength: ray.wavelength,
+            front_face: false,
+            material,
+        };
+        hit.set_face_normal(ray, outward_normal);
+        hit.geometric_normal = hit.normal;
+        hit
+    }
+
+    /// Shading normal as it was before being flipped towards the ray
+    pub fn outward_normal(&self) -> Vector3 {
+        if self.front_face {
+            self.normal
+        } else {
+            -self.normal
+        }
+    }
+
+    /// Shading frame with `u` along the surface tangent when available
+    pub fn shading_frame(&self) -> Onb {
+        Onb::from_normal_tangent(&self.normal, &self.tangent)
+    }
+
     pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: Vector3) {
         self.front_face = Vector3::dot(&ray.direction, &outward_normal) < 0.;
         self.normal = if self.front_face {
@@ -67,18 +138,24 @@ impl HitTarget {
     pub fn new() -> Self {
         Self { targets: vec![] }
     }
-}
 
-impl Hit for HitTarget {
-    fn hit(&self, ray: &Ray, mut range: (f64, f64)) -> Option<RayHit> {
-        let mut ray_hit: Option<RayHit> = None;
+    /// Closest hit along with the index of the object that was hit
+    pub fn hit_indexed(&self, ray: &Ray, mut range: (f64, f64)) -> Option<(usize, RayHit)> {
+        let mut ray_hit: Option<(usize, RayHit)> = None;
         let mut closest_hit_distance = range.1;
 
-        for object in self.targets.iter() {
+        for (index, object) in self.targets.iter().enumerate() {
             range = (range.0, closest_hit_distance);
-            if let Some(local_hit) = object.hit(ray, range) {
+            // ? Masked hits are skipped by searching again just past them
+            let mut search = range;
+            while let Some(local_hit) = object.hit(ray, search) {
+                if local_hit.material.masked(&local_hit) {
+                    search.0 = local_hit.t.next_up();
+                    continue;
+                }
                 closest_hit_distance = local_hit.t;
-                ray_hit = Some(local_hit);
+                ray_hit = Some((index, local_hit));
+                break;
             }
         }
 
@@ -86,6 +163,12 @@ impl Hit for HitTarget {
     }
 }
 
+impl Hit for HitTarget {
+    fn hit(&self, ray: &Ray, range: (f64, f64)) -> Option<RayHit> {
+        self.hit_indexed(ray, range).map(|(_, hit)| hit)
+    }
+}
+
 impl Deref for HitTarget {
     type Target = Vec<Arc<dyn Hit>>;
 